@@ -0,0 +1,359 @@
+//! `#[derive(CbmlCodable)]`, 生成 cbml::CbmlCodable 的实现.
+#![allow(clippy::needless_return)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{
+    Attribute, Data, DeriveInput, Fields, GenericParam, Ident, LitStr, Path, parse_macro_input,
+    parse_quote,
+};
+
+#[proc_macro_derive(CbmlCodable, attributes(cbml))]
+pub fn derive_cbml_codable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand(input) {
+        Ok(x) => x.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// cbml 的关键字, 跟 cbml::lexer::token::TokenKind::handle_keyword 相同.
+const KEYWORDS: &[&str] = &[
+    "true", "false", "none", "any", "struct", "union", "todo", "use", "default", "enum", "type",
+    "string", "number", "int", "bool",
+];
+
+/// `#[cbml(...)]` 中的内容.
+#[derive(Default)]
+struct CbmlAttrs {
+    /// `#[cbml(rename = "name")]`
+    rename: Option<String>,
+
+    /// `#[cbml(default)]` 是 Some(None), `#[cbml(default = "func")]` 是 Some(Some(func)).
+    default: Option<Option<Path>>,
+
+    /// `#[cbml(skip)]`
+    skip: bool,
+}
+
+impl CbmlAttrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut re = Self::default();
+
+        for attr in attrs.iter().filter(|x| x.path().is_ident("cbml")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    re.rename = Some(s.value());
+                } else if meta.path.is_ident("default") {
+                    if meta.input.peek(syn::Token![=]) {
+                        let s: LitStr = meta.value()?.parse()?;
+                        re.default = Some(Some(s.parse()?));
+                    } else {
+                        re.default = Some(None);
+                    }
+                } else if meta.path.is_ident("skip") {
+                    re.skip = true;
+                } else {
+                    return Err(meta
+                        .error("unknown cbml attribute, expected `rename`, `default` or `skip`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        return Ok(re);
+    }
+
+    /// 在 cbml 中的名字, `r#type` 在 cbml 中是 `type`.
+    fn name(&self, ident: &Ident) -> String {
+        match &self.rename {
+            Some(x) => x.clone(),
+            None => ident.unraw().to_string(),
+        }
+    }
+
+    /// enum 的成员名字在值的位置上使用, 不能是关键字, 需要使用 rename.
+    fn variant_name(&self, ident: &Ident) -> syn::Result<String> {
+        let name = self.name(ident);
+        if self.rename.is_none() && KEYWORDS.contains(&name.as_str()) {
+            return Err(syn::Error::new(
+                ident.span(),
+                format!(
+                    "`{}` is a keyword in cbml, use `#[cbml(rename = \"...\")]` to give it another name",
+                    name
+                ),
+            ));
+        }
+
+        return Ok(name);
+    }
+
+    /// 没有这个字段时使用的值.
+    fn default_expr(&self) -> TokenStream2 {
+        match &self.default {
+            Some(Some(func)) => quote!(#func()),
+            _ => quote!(::core::default::Default::default()),
+        }
+    }
+}
+
+fn expand(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    // 每一个泛型参数都需要实现 CbmlCodable.
+    for param in input.generics.params.iter_mut() {
+        if let GenericParam::Type(ty) = param {
+            ty.bounds.push(parse_quote!(::cbml::CbmlCodable));
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let (from_body, to_body) = match &input.data {
+        Data::Struct(data) => {
+            let from = from_fields(&data.fields, quote!(Self), quote!(val))?;
+            let to = to_fields(&data.fields)?;
+            let to = match &data.fields {
+                Fields::Unit => to,
+                fields => {
+                    let pattern = fields_pattern(fields, quote!(Self));
+                    quote! {
+                        let #pattern = self;
+                        #to
+                    }
+                }
+            };
+            (from, to)
+        }
+        Data::Enum(data) => {
+            let mut variant_names: Vec<String> = Vec::new();
+            let mut from_arms: Vec<TokenStream2> = Vec::new();
+            let mut to_arms: Vec<TokenStream2> = Vec::new();
+
+            for v in &data.variants {
+                let attrs = CbmlAttrs::parse(&v.attrs)?;
+                if attrs.skip || attrs.default.is_some() {
+                    return Err(syn::Error::new(
+                        v.span(),
+                        "only `rename` can be used on enum variants",
+                    ));
+                }
+
+                let ident = &v.ident;
+                let variant_name = attrs.variant_name(ident)?;
+                variant_names.push(variant_name.clone());
+
+                if let Fields::Unit = v.fields {
+                    // fast
+                    from_arms.push(quote! {
+                        ::cbml::CbmlValue::EnumUnit(name) if name == #variant_name => {
+                            ::core::result::Result::Ok(Self::#ident)
+                        }
+                    });
+                    to_arms.push(quote! {
+                        Self::#ident => ::cbml::CbmlValue::EnumUnit(#variant_name.to_string())
+                    });
+                    continue;
+                }
+
+                // git(url)
+                let from = from_fields(&v.fields, quote!(Self::#ident), quote!(*payload))?;
+                from_arms.push(quote! {
+                    ::cbml::CbmlValue::EnumField(name, payload) if name == #variant_name => {
+                        (|| -> ::core::result::Result<Self, ::cbml::CodableError> { #from })()
+                            .map_err(|e| e.in_field(#variant_name))
+                    }
+                });
+
+                let to = to_fields(&v.fields)?;
+                let pattern = fields_pattern(&v.fields, quote!(Self::#ident));
+                to_arms.push(quote! {
+                    #pattern => {
+                        let payload = { #to };
+                        ::cbml::CbmlValue::EnumField(#variant_name.to_string(), ::std::boxed::Box::new(payload))
+                    }
+                });
+            }
+
+            let from = quote! {
+                match val {
+                    #(#from_arms)*
+                    x => ::core::result::Result::Err(
+                        ::cbml::cbml_codable::__private::unknown_variant(&x, &[#(#variant_names),*])
+                    ),
+                }
+            };
+            let to = quote! {
+                match self {
+                    #(#to_arms,)*
+                }
+            };
+            (from, to)
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new(
+                input.span(),
+                "CbmlCodable can not be derived for unions",
+            ));
+        }
+    };
+
+    return Ok(quote! {
+        impl #impl_generics ::cbml::CbmlCodable for #name #ty_generics #where_clause {
+            fn from_cbml_value(
+                val: ::cbml::CbmlValue,
+            ) -> ::core::result::Result<Self, ::cbml::CodableError> {
+                #from_body
+            }
+
+            fn to_cbml_value(self) -> ::cbml::CbmlValue {
+                #to_body
+            }
+        }
+    });
+}
+
+/// 从 val 构造 constructor, 例如 `Self { a, b }`, `Self::Git(url)`.
+fn from_fields(
+    fields: &Fields,
+    constructor: TokenStream2,
+    val: TokenStream2,
+) -> syn::Result<TokenStream2> {
+    let private = quote!(::cbml::cbml_codable::__private);
+
+    match fields {
+        Fields::Named(named) => {
+            let mut inits: Vec<TokenStream2> = Vec::new();
+
+            for f in &named.named {
+                let attrs = CbmlAttrs::parse(&f.attrs)?;
+                let ident = f.ident.as_ref().unwrap();
+                let name = attrs.name(ident);
+
+                let init = if attrs.skip {
+                    let default = attrs.default_expr();
+                    quote!(#default)
+                } else if attrs.default.is_some() {
+                    let default = attrs.default_expr();
+                    quote!(#private::field_or_else(&mut fields, #name, || #default)?)
+                } else {
+                    quote!(#private::field(&mut fields, #name)?)
+                };
+                inits.push(quote!(#ident: #init));
+            }
+
+            return Ok(quote! {
+                let mut fields = #private::struct_fields(#val)?;
+                ::core::result::Result::Ok(#constructor { #(#inits),* })
+            });
+        }
+        Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+            check_no_attrs(fields)?;
+            return Ok(quote! {
+                ::core::result::Result::Ok(#constructor(::cbml::CbmlCodable::from_cbml_value(#val)?))
+            });
+        }
+        Fields::Unnamed(unnamed) => {
+            check_no_attrs(fields)?;
+            let len = unnamed.unnamed.len();
+            let elements =
+                (0..len).map(|i| quote!(#private::element(elements.next().unwrap(), #i)?));
+
+            return Ok(quote! {
+                let mut elements = #private::tuple_elements(#val, #len)?.into_iter();
+                ::core::result::Result::Ok(#constructor(#(#elements),*))
+            });
+        }
+        Fields::Unit => {
+            return Ok(quote! {
+                match #val {
+                    ::cbml::CbmlValue::None => ::core::result::Result::Ok(#constructor),
+                    x => ::core::result::Result::Err(::cbml::CodableError::mismatched_types("none", &x)),
+                }
+            });
+        }
+    }
+}
+
+/// 将 `fields_pattern` 绑定的字段转换为 CbmlValue.
+fn to_fields(fields: &Fields) -> syn::Result<TokenStream2> {
+    let private = quote!(::cbml::cbml_codable::__private);
+
+    match fields {
+        Fields::Named(named) => {
+            let mut pushes: Vec<TokenStream2> = Vec::new();
+
+            for f in &named.named {
+                let attrs = CbmlAttrs::parse(&f.attrs)?;
+                let ident = f.ident.as_ref().unwrap();
+                let binding = format_ident!("__{}", ident.unraw());
+                if attrs.skip {
+                    pushes.push(quote!(let _ = #binding;));
+                    continue;
+                }
+
+                let name = attrs.name(ident);
+                pushes.push(quote! {
+                    fields.push((#name.to_string(), ::cbml::CbmlCodable::to_cbml_value(#binding)));
+                });
+            }
+
+            return Ok(quote! {
+                let mut fields: ::std::vec::Vec<(::std::string::String, ::cbml::CbmlValue)> = ::std::vec::Vec::new();
+                #(#pushes)*
+                #private::struct_value(fields)
+            });
+        }
+        Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+            return Ok(quote! {
+                ::cbml::CbmlCodable::to_cbml_value(__0)
+            });
+        }
+        Fields::Unnamed(unnamed) => {
+            let bindings = (0..unnamed.unnamed.len()).map(|i| format_ident!("__{}", i));
+            return Ok(quote! {
+                ::cbml::CbmlValue::Array(::std::vec![#(::cbml::CbmlCodable::to_cbml_value(#bindings)),*])
+            });
+        }
+        Fields::Unit => {
+            return Ok(quote!(::cbml::CbmlValue::None));
+        }
+    }
+}
+
+/// `Self { a: __a, b: __b }` 或者 `Self::Git(__0)`.
+fn fields_pattern(fields: &Fields, constructor: TokenStream2) -> TokenStream2 {
+    match fields {
+        Fields::Named(named) => {
+            let bindings = named.named.iter().map(|f| {
+                let ident = f.ident.as_ref().unwrap();
+                let binding = format_ident!("__{}", ident.unraw());
+                quote!(#ident: #binding)
+            });
+            quote!(#constructor { #(#bindings),* })
+        }
+        Fields::Unnamed(unnamed) => {
+            let bindings = (0..unnamed.unnamed.len()).map(|i| format_ident!("__{}", i));
+            quote!(#constructor(#(#bindings),*))
+        }
+        Fields::Unit => quote!(#constructor),
+    }
+}
+
+/// tuple 的元素没有名字, 所以不能使用属性.
+fn check_no_attrs(fields: &Fields) -> syn::Result<()> {
+    for f in fields.iter() {
+        if f.attrs.iter().any(|x| x.path().is_ident("cbml")) {
+            return Err(syn::Error::new(
+                f.span(),
+                "cbml attributes can only be used on named fields",
+            ));
+        }
+    }
+
+    return Ok(());
+}
@@ -1,170 +1,415 @@
-// use crate::CbmlValue;
-
-// /// ```
-// ///
-// /// use cbml::*;
-// ///
-// /// #[derive(CbmlCodable, Debug, PartialEq, Clone)]
-// /// struct MyConfig {
-// ///     name: String,
-// ///     age: f64,
-// ///     b: bool,
-// /// }
-// ///
-// /// fn asdfsadf() {
-// ///     let a = MyConfig {
-// ///         name: "namasdfsadfsadfe".to_string(),
-// ///         age: 99.0,
-// ///         b: false,
-// ///     };
-// ///
-// ///    let cbml_code: String = a.clone().to_cbml();
-// ///    let b = MyConfig::from_cbml_value(&cbml_code);
-// ///
-// ///     assert_eq!(&a, &b);
-// /// }
-// /// ```
-// pub trait CbmlCodable {
-//     fn from_cbml_value(val: CbmlValue) -> Result<Self, ()>
-//     where
-//         Self: Sized;
-
-//     fn to_cbml_value(self) -> CbmlValue;
-// }
-
-// pub trait CbmlCodable2 {
-//     fn from_cbml(str: &str);
-//     fn to_cbml() -> String;
-// }
-
-// #[derive(Debug, Clone, PartialEq)]
-// pub struct AnyCbmlValue(pub CbmlValue);
-
-// impl CbmlCodable for AnyCbmlValue {
-//     fn from_cbml_value(val: CbmlValue) -> Result<Self, ()>
-//     where
-//         Self: Sized,
-//     {
-//         Ok(Self(val))
-//     }
-
-//     fn to_cbml_value(self) -> CbmlValue {
-//         self.0
-//     }
-// }
-
-// impl CbmlCodable for String {
-//     fn from_cbml_value(val: CbmlValue) -> Result<Self, ()>
-//     where
-//         Self: Sized,
-//     {
-//         match val {
-//             CbmlValue::String(s) => Ok(s),
-//             _ => Err(()),
-//         }
-//     }
-
-//     fn to_cbml_value(self) -> CbmlValue {
-//         CbmlValue::String(self)
-//     }
-// }
-
-// impl CbmlCodable for bool {
-//     fn from_cbml_value(val: CbmlValue) -> Result<Self, ()> {
-//         match val {
-//             CbmlValue::Boolean(s) => Ok(s),
-//             // CbmlValue::None => Ok(None),
-//             _ => Err(()),
-//         }
-//     }
-
-//     fn to_cbml_value(self) -> CbmlValue {
-//         CbmlValue::Boolean(self)
-//     }
-// }
-
-// impl CbmlCodable for f64 {
-//     fn from_cbml_value(val: CbmlValue) -> Result<Self, ()> {
-//         match val {
-//             CbmlValue::Number(s) => Ok(s),
-
-//             _ => Err(()),
-//         }
-//     }
-
-//     fn to_cbml_value(self) -> CbmlValue {
-//         CbmlValue::Number(self)
-//     }
-// }
-
-// impl<T: CbmlCodable + Sized> CbmlCodable for Option<T> {
-//     fn from_cbml_value(val: CbmlValue) -> Result<Self, ()>
-//     where
-//         Self: Sized,
-//     {
-//         if val.cbml_none().is_some() {
-//             return Ok(None);
-//         }
-
-//         let re = T::from_cbml_value(val);
-//         match re {
-//             Ok(v) => Ok(Some(v)),
-//             Err(_) => Err(()),
-//         }
-//     }
-
-//     fn to_cbml_value(self) -> CbmlValue {
-//         match self {
-//             Some(v) => v.to_cbml_value(),
-//             None => CbmlValue::None,
-//         }
-//     }
-// }
-
-// impl<T: CbmlCodable + Sized> CbmlCodable for Vec<T> {
-//     fn from_cbml_value(val: CbmlValue) -> Result<Self, ()>
-//     where
-//         Self: Sized,
-//     {
-//         let re = val.cbml_array();
-//         match re {
-//             Some(v) => {
-//                 let mut re: Vec<T> = Vec::new();
-//                 for x in v {
-//                     let Ok(inner_val) = T::from_cbml_value(x.clone()) else {
-//                         return Err(());
-//                     };
-
-//                     re.push(inner_val);
-//                 }
-
-//                 return Ok(re);
-//             }
-//             None => Err(()),
-//         }
-//     }
-
-//     fn to_cbml_value(self) -> CbmlValue {
-//         let mut re: Vec<CbmlValue> = Vec::new();
-
-//         for x in self {
-//             re.push(x.to_cbml_value());
-//         }
-
-//         return CbmlValue::Array(re);
-//     }
-// }
-
-// impl<T: CbmlCodable + Sized> CbmlCodable for Box<T> {
-//     fn from_cbml_value(val: CbmlValue) -> Result<Self, ()>
-//     where
-//         Self: Sized,
-//     {
-//         let a = T::from_cbml_value(val)?;
-//         return Ok(Box::new(a));
-//     }
-
-//     fn to_cbml_value(self) -> CbmlValue {
-//         (*self).to_cbml_value()
-//     }
-// }
+//! 不使用 serde 时, 通过 `#[derive(CbmlCodable)]` 在 CbmlValue 和 rust 的类型之间转换.
+use crate::CbmlValue;
+use indexmap::IndexMap;
+use std::fmt;
+
+/// ```
+/// use cbml::*;
+///
+/// #[derive(CbmlCodable, Debug, PartialEq, Clone)]
+/// struct MyConfig {
+///     name: String,
+///     #[cbml(rename = "年龄")]
+///     age: f64,
+///     #[cbml(default)]
+///     b: bool,
+///     #[cbml(skip)]
+///     cache: Vec<String>,
+/// }
+///
+/// let a = MyConfig {
+///     name: "cbml".to_string(),
+///     age: 99.0,
+///     b: false,
+///     cache: vec![],
+/// };
+///
+/// let val: CbmlValue = a.clone().to_cbml_value();
+/// let b = MyConfig::from_cbml_value(val).unwrap();
+///
+/// assert_eq!(&a, &b);
+/// ```
+/// 字段的属性:
+/// - `#[cbml(rename = "name")]` 在 cbml 中使用另一个名字, 也可以用在 enum 的成员上.
+/// - `#[cbml(default)]` 没有这个字段时使用 Default::default(),
+///   `#[cbml(default = "path::to::func")]` 使用函数的返回值.
+/// - `#[cbml(skip)]` 不输出这个字段, 读取时使用 Default::default().
+pub trait CbmlCodable {
+    fn from_cbml_value(val: CbmlValue) -> Result<Self, CodableError>
+    where
+        Self: Sized;
+
+    fn to_cbml_value(self) -> CbmlValue;
+}
+
+/// from_cbml_value 的错误.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodableError {
+    /// 出错的字段的路径, 例如 `deps.serde.version`, 最外层出错时是空字符串.
+    pub path: String,
+    pub msg: String,
+}
+
+impl CodableError {
+    pub fn new(msg: String) -> Self {
+        Self {
+            path: String::new(),
+            msg,
+        }
+    }
+
+    /// expected: 需要的类型, 例如 `string`.
+    pub fn mismatched_types(expected: &str, found: &CbmlValue) -> Self {
+        Self::new(format!(
+            "mismatched types, expected `{}` found `{}`",
+            expected,
+            crate::ToCbml::to_cbml(found, 0)
+        ))
+    }
+
+    /// 错误从里往外传, 每一层在路径的前面加上自己的字段名.
+    pub fn in_field(mut self, name: &str) -> Self {
+        self.path = if self.path.is_empty() {
+            name.to_string()
+        } else if self.path.starts_with('[') {
+            format!("{}{}", name, self.path)
+        } else {
+            format!("{}.{}", name, self.path)
+        };
+
+        return self;
+    }
+
+    pub fn in_index(self, index: usize) -> Self {
+        self.in_field(&format!("[{}]", index))
+    }
+}
+
+impl fmt::Display for CodableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.msg)
+        } else {
+            write!(f, "{} at `{}`", self.msg, self.path)
+        }
+    }
+}
+
+impl std::error::Error for CodableError {}
+
+/// 以下的函数由 `#[derive(CbmlCodable)]` 生成的代码使用.
+#[doc(hidden)]
+pub mod __private {
+    use super::*;
+
+    /// 结构体的字段, 按照结构体中定义的顺序.
+    pub fn struct_value(fields: Vec<(String, CbmlValue)>) -> CbmlValue {
+        CbmlValue::Struct(fields.into_iter().collect())
+    }
+
+    pub fn struct_fields(val: CbmlValue) -> Result<IndexMap<String, CbmlValue>, CodableError> {
+        match val {
+            CbmlValue::Struct(fields) => Ok(fields),
+            CbmlValue::Map(pairs) => Ok(pairs.into_iter().collect()),
+            x => Err(CodableError::mismatched_types("struct", &x)),
+        }
+    }
+
+    /// 没有这个字段时当作 `none`, 这样 Option 可以省略.
+    pub fn field<T: CbmlCodable>(
+        fields: &mut IndexMap<String, CbmlValue>,
+        name: &str,
+    ) -> Result<T, CodableError> {
+        let Some(val) = fields.shift_remove(name) else {
+            return T::from_cbml_value(CbmlValue::None)
+                .map_err(|_| CodableError::new(format!("missing field `{}`", name)));
+        };
+
+        return T::from_cbml_value(val).map_err(|e| e.in_field(name));
+    }
+
+    /// `#[cbml(default)]` 的字段.
+    pub fn field_or_else<T: CbmlCodable>(
+        fields: &mut IndexMap<String, CbmlValue>,
+        name: &str,
+        default: impl FnOnce() -> T,
+    ) -> Result<T, CodableError> {
+        match fields.shift_remove(name) {
+            Some(val) => T::from_cbml_value(val).map_err(|e| e.in_field(name)),
+            None => Ok(default()),
+        }
+    }
+
+    /// tuple 的元素, 例如 `git(["url", "main"])` 中的数组.
+    pub fn tuple_elements(val: CbmlValue, len: usize) -> Result<Vec<CbmlValue>, CodableError> {
+        match val {
+            CbmlValue::Array(values) if values.len() == len => Ok(values),
+            x => Err(CodableError::mismatched_types(
+                &format!("array of {} elements", len),
+                &x,
+            )),
+        }
+    }
+
+    pub fn element<T: CbmlCodable>(val: CbmlValue, index: usize) -> Result<T, CodableError> {
+        T::from_cbml_value(val).map_err(|e| e.in_index(index))
+    }
+
+    /// enum 的成员名字不在 variants 中.
+    pub fn unknown_variant(found: &CbmlValue, variants: &[&str]) -> CodableError {
+        let name = match found {
+            CbmlValue::EnumField(name, _) | CbmlValue::EnumUnit(name) => name,
+            x => return CodableError::mismatched_types("enum", x),
+        };
+
+        let variants: Vec<String> = variants.iter().map(|x| format!("`{}`", x)).collect();
+        CodableError::new(format!(
+            "unknown variant `{}`, expected one of {}",
+            name,
+            variants.join(", ")
+        ))
+    }
+}
+
+impl CbmlCodable for CbmlValue {
+    fn from_cbml_value(val: CbmlValue) -> Result<Self, CodableError> {
+        Ok(val)
+    }
+
+    fn to_cbml_value(self) -> CbmlValue {
+        self
+    }
+}
+
+impl CbmlCodable for String {
+    fn from_cbml_value(val: CbmlValue) -> Result<Self, CodableError> {
+        match val {
+            CbmlValue::String(s) => Ok(s),
+            x => Err(CodableError::mismatched_types("string", &x)),
+        }
+    }
+
+    fn to_cbml_value(self) -> CbmlValue {
+        CbmlValue::String(self)
+    }
+}
+
+impl CbmlCodable for bool {
+    fn from_cbml_value(val: CbmlValue) -> Result<Self, CodableError> {
+        match val {
+            CbmlValue::Boolean(s) => Ok(s),
+            x => Err(CodableError::mismatched_types("bool", &x)),
+        }
+    }
+
+    fn to_cbml_value(self) -> CbmlValue {
+        CbmlValue::Boolean(self)
+    }
+}
+
+impl CbmlCodable for f64 {
+    fn from_cbml_value(val: CbmlValue) -> Result<Self, CodableError> {
+        match val {
+            CbmlValue::Number(s) => Ok(s),
+            x => Err(CodableError::mismatched_types("number", &x)),
+        }
+    }
+
+    fn to_cbml_value(self) -> CbmlValue {
+        CbmlValue::Number(self)
+    }
+}
+
+impl<T: CbmlCodable + Sized> CbmlCodable for Option<T> {
+    fn from_cbml_value(val: CbmlValue) -> Result<Self, CodableError>
+    where
+        Self: Sized,
+    {
+        if val.cbml_none().is_some() {
+            return Ok(None);
+        }
+
+        return T::from_cbml_value(val).map(Some);
+    }
+
+    fn to_cbml_value(self) -> CbmlValue {
+        match self {
+            Some(v) => v.to_cbml_value(),
+            None => CbmlValue::None,
+        }
+    }
+}
+
+impl<T: CbmlCodable + Sized> CbmlCodable for Vec<T> {
+    fn from_cbml_value(val: CbmlValue) -> Result<Self, CodableError>
+    where
+        Self: Sized,
+    {
+        let CbmlValue::Array(values) = val else {
+            return Err(CodableError::mismatched_types("array", &val));
+        };
+
+        let mut re: Vec<T> = Vec::new();
+        for (i, x) in values.into_iter().enumerate() {
+            re.push(T::from_cbml_value(x).map_err(|e| e.in_index(i))?);
+        }
+
+        return Ok(re);
+    }
+
+    fn to_cbml_value(self) -> CbmlValue {
+        let mut re: Vec<CbmlValue> = Vec::new();
+
+        for x in self {
+            re.push(x.to_cbml_value());
+        }
+
+        return CbmlValue::Array(re);
+    }
+}
+
+impl<T: CbmlCodable + Sized> CbmlCodable for Box<T> {
+    fn from_cbml_value(val: CbmlValue) -> Result<Self, CodableError>
+    where
+        Self: Sized,
+    {
+        let a = T::from_cbml_value(val)?;
+        return Ok(Box::new(a));
+    }
+
+    fn to_cbml_value(self) -> CbmlValue {
+        (*self).to_cbml_value()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CbmlCodable, CbmlValue, CodableError, ToCbml};
+
+    fn default_port() -> f64 {
+        80.0
+    }
+
+    #[derive(CbmlCodable, Debug, PartialEq)]
+    enum Source {
+        #[cbml(rename = "git")]
+        Git {
+            url: String,
+            branch: Option<String>,
+            // 在 cbml 中是 `ref`.
+            r#ref: Option<String>,
+        },
+        #[cbml(rename = "path")]
+        Path(String),
+        #[cbml(rename = "registry")]
+        Registry,
+    }
+
+    #[derive(CbmlCodable, Debug, PartialEq)]
+    struct Dep {
+        name: String,
+        source: Box<Source>,
+    }
+
+    #[derive(CbmlCodable, Debug, PartialEq)]
+    struct Config {
+        name: String,
+        #[cbml(default = "default_port")]
+        port: f64,
+        #[cbml(default)]
+        debug: bool,
+        owner: Option<String>,
+        #[cbml(rename = "dependencies")]
+        deps: Vec<Dep>,
+        #[cbml(skip)]
+        cache: Vec<String>,
+        // 关键字可以作为字段名.
+        r#type: Option<String>,
+    }
+
+    fn parse(code: &str) -> CbmlValue {
+        crate::cbml_project::cbml_file::CbmlFile::new_from(String::new(), code)
+            .try_to_cbml_value()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_derive_cbml_codable() {
+        let code = r##"
+name = "cbml"
+type = "app"
+dependencies = [
+    { name = "a", source = git({ url = "https://example.com/a.git", branch = none, ref = "v1" }) },
+    { name = "b", source = path("../b") },
+    { name = "c", source = registry }
+]
+"##;
+        let c = Config::from_cbml_value(parse(code)).unwrap();
+        assert_eq!(c.port, 80.0);
+        assert!(!c.debug);
+        assert_eq!(c.owner, None);
+        assert_eq!(c.r#type.as_deref(), Some("app"));
+        assert_eq!(
+            *c.deps[0].source,
+            Source::Git {
+                url: "https://example.com/a.git".into(),
+                branch: None,
+                r#ref: Some("v1".into()),
+            }
+        );
+        assert_eq!(*c.deps[1].source, Source::Path("../b".into()));
+        assert_eq!(*c.deps[2].source, Source::Registry);
+
+        // 字段按照定义的顺序输出, skip 的字段不输出.
+        let code = Config {
+            cache: vec!["x".into()],
+            ..c
+        }
+        .to_cbml_value()
+        .to_cbml(0);
+        assert!(
+            code.starts_with("{\n    name = \"cbml\"\n    port = 80\n"),
+            "{}",
+            code
+        );
+        assert!(!code.contains("cache"));
+        assert!(code.contains("source = git({\n"), "{}", code);
+
+        let c2 = Config::from_cbml_value(
+            parse(&format!("a = {}", code)).cbml_struct().unwrap()["a"].clone(),
+        )
+        .unwrap();
+        assert_eq!(c2.deps.len(), 3);
+        assert_eq!(c2.cache, Vec::<String>::new());
+        assert_eq!(c2.r#type.as_deref(), Some("app"));
+    }
+
+    #[test]
+    fn test_derive_errors() {
+        let e = Config::from_cbml_value(parse("port = 1")).unwrap_err();
+        assert_eq!(e.to_string(), "missing field `name`");
+
+        let code = r##"
+name = "cbml"
+dependencies = [{ name = "a", source = git({ url = 1 }) }]
+"##;
+        let e = Config::from_cbml_value(parse(code)).unwrap_err();
+        assert_eq!(
+            e,
+            CodableError {
+                path: "dependencies[0].source.git.url".into(),
+                msg: "mismatched types, expected `string` found `1`".into(),
+            }
+        );
+
+        let code = r##"
+name = "cbml"
+dependencies = [{ name = "a", source = svn("x") }]
+"##;
+        let e = Config::from_cbml_value(parse(code)).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "unknown variant `svn`, expected one of `git`, `path`, `registry` at `dependencies[0].source`"
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use crate::ToCbml;
 
-use super::cbml_value::CbmlValue;
+use super::cbml_value::{CbmlValue, string_to_cbml};
+use super::string_format::StringFormat;
 
 /// cbml 的类型定义,
 /// .def.cbml
@@ -26,11 +27,18 @@ pub enum CbmlType {
     ///
     /// 支持 unicode:  
     /// "hello, \u{4f60}\u{597d}"  
-    String,
+    ///
+    /// 可以添加约束: `string(1..=64)` `string("^[a-z]+$")` `string(email)`  
+    String { constraints: Vec<StringConstraint> },
 
     /// 这个类型是一个特殊的类型, 用来表示数字.  
     /// 123, -123, 0.123, -0.123, 0xfff  0110101010  
-    Number,
+    /// 可以限制范围: `number(0..1)`  
+    Number { range: Option<NumberRange> },
+
+    /// 整数, 值是没有小数部分的 number.  
+    /// 可以限制范围: `int(1..=65535)`  
+    Int { range: Option<NumberRange> },
 
     /// 这个类型是一个特殊的类型, 用来表示布尔值.  
     /// true or false  
@@ -45,7 +53,11 @@ pub enum CbmlType {
     /// [bool] -> [true, false]  
     /// [any] -> [1, "hello", true, [1,2,3], {name = "hello"}]  
     ///
-    Array { inner_type: Box<CbmlType> },
+    /// 可以添加约束: `[string](1..)` `[number](..=10, unique)`  
+    Array {
+        inner_type: Box<CbmlType>,
+        constraints: Vec<ArrayConstraint>,
+    },
 
     /// 这个类型是一个特殊的类型, 用来表示联合类型.  
     /// 1 | 2 | 3 | "hello" | false | [1,2,3] | {name = "hello"}  
@@ -54,6 +66,28 @@ pub enum CbmlType {
     /// 1 | 1 | 2  这样的事不允许的.  
     Union { allowed_values: Vec<CbmlValue> },
 
+    /// 由类型组成的联合类型, 值只需要符合其中一个类型.  
+    /// ```cbml
+    /// port: number | string
+    /// source: Git | Dir
+    /// ```
+    /// 赋值:  
+    /// ```cbml
+    /// port = 8080
+    /// source = { path = "/tmp" }
+    /// ```
+    TypeUnion { types: Vec<CbmlType> },
+
+    /// 键值对, key 只能是 string, 赋值时写成结构体字面量, 保留书写顺序.  
+    /// ```cbml
+    /// env: { [string]: string }
+    /// ```
+    /// 赋值:  
+    /// ```cbml
+    /// env = { HOME = "/root", LANG = "C" }
+    /// ```
+    Map { value_type: Box<CbmlType> },
+
     /// 这个类型是一个特殊的类型, 用来表示可选类型.  
     /// ?string -> none  
     /// ?string -> "str"  
@@ -96,18 +130,57 @@ pub enum CbmlType {
     /// ```cbml
     /// who = 张三({name = "zhangsan", age = 18})
     /// ```
-    Enum { fields: Vec<(String, CbmlType)> },
+    /// 没有值的成员的类型是 None: `enum { fast\n slow }`
+    Enum {
+        fields: Vec<(String, Option<CbmlType>)>,
+    },
+
+    /// 引用一个具名类型, 用来表示递归类型.  
+    /// 类型检查时才通过 DefCbmlFile::get_type_by_name 获取具体的类型.  
+    /// ```cbml
+    /// struct Node {
+    ///     name: string
+    ///     children: [Node]
+    /// }
+    /// ```
+    Named { type_name: String },
 }
 
 impl ToCbml for CbmlType {
     fn to_cbml(&self, deepth: usize) -> String {
         match self {
-            CbmlType::String => format!("string"),
-            CbmlType::Number => format!("number"),
+            CbmlType::String { constraints } => {
+                if constraints.is_empty() {
+                    return format!("string");
+                }
+
+                let constraints: Vec<String> = constraints.iter().map(|x| x.to_cbml(0)).collect();
+                format!("string({})", constraints.join(", "))
+            }
+            CbmlType::Number { range } => match range {
+                Some(range) => format!("number({})", range),
+                None => format!("number"),
+            },
+            CbmlType::Int { range } => match range {
+                Some(range) => format!("int({})", range),
+                None => format!("int"),
+            },
             CbmlType::Bool => format!("bool"),
             CbmlType::Any => format!("any"),
-            CbmlType::Array { inner_type } => {
-                format!("[{}]", inner_type.to_cbml(deepth))
+            CbmlType::Array {
+                inner_type,
+                constraints,
+            } => {
+                if constraints.is_empty() {
+                    return format!("[{}]", inner_type.to_cbml(deepth));
+                }
+
+                let constraints: Vec<String> = constraints.iter().map(|x| x.to_cbml(0)).collect();
+                format!(
+                    "[{}]({})",
+                    inner_type.to_cbml(deepth),
+                    constraints.join(", ")
+                )
             }
             CbmlType::Union { allowed_values } => {
                 let mut re = String::new();
@@ -125,6 +198,13 @@ impl ToCbml for CbmlType {
 
                 return re;
             }
+            CbmlType::TypeUnion { types } => {
+                let types: Vec<String> = types.iter().map(|x| x.to_cbml(deepth)).collect();
+                return types.join(" | ");
+            }
+            CbmlType::Map { value_type } => {
+                format!("{{ [string]: {} }}", value_type.to_cbml(deepth))
+            }
             CbmlType::Optional { inner_type } => {
                 format!("?{}", inner_type.to_cbml(deepth))
             }
@@ -153,17 +233,175 @@ impl ToCbml for CbmlType {
                     let field_name = &x.0;
                     let field_type = &x.1;
 
-                    re.push_str(&format!(
-                        "{}{}({})\n",
-                        "    ".repeat(deepth + 1),
-                        field_name,
-                        field_type.to_cbml(deepth + 1)
-                    ));
+                    re.push_str(&"    ".repeat(deepth + 1));
+                    re.push_str(field_name);
+                    if let Some(field_type) = field_type {
+                        re.push_str(&format!("({})", field_type.to_cbml(deepth + 1)));
+                    }
+                    re.push('\n');
                 }
                 re.push_str(&"    ".repeat(deepth));
                 re.push_str("}");
                 return re;
             }
+            CbmlType::Named { type_name } => type_name.clone(),
+        }
+    }
+}
+
+/// number 和 int 的取值范围.  
+/// `1..10` `1..=10` `1..` `..10` `..=10`  
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumberRange {
+    pub start: Option<f64>,
+    pub end: Option<f64>,
+    /// `..=` 包含 end.
+    pub inclusive: bool,
+}
+
+impl NumberRange {
+    pub fn contains(&self, n: f64) -> bool {
+        if let Some(start) = self.start {
+            if n < start {
+                return false;
+            }
+        }
+
+        if let Some(end) = self.end {
+            if self.inclusive && n > end {
+                return false;
+            }
+            if !self.inclusive && n >= end {
+                return false;
+            }
+        }
+
+        return true;
+    }
+
+    /// 范围中没有任何值, 例如 `10..1` `1..1`.
+    pub fn is_empty(&self) -> bool {
+        match (self.start, self.end) {
+            (Some(start), Some(end)) => {
+                if self.inclusive {
+                    start > end
+                } else {
+                    start >= end
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for NumberRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(start) = self.start {
+            write!(f, "{}", start)?;
+        }
+
+        write!(f, "{}", if self.inclusive { "..=" } else { ".." })?;
+
+        if let Some(end) = self.end {
+            write!(f, "{}", end)?;
+        }
+
+        return Ok(());
+    }
+}
+
+/// string 的约束.  
+/// `string(1..=64, hostname)` `string("^[a-z]+$")`  
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringConstraint {
+    /// 字符数量的范围, `string(1..)` 表示不能是空字符串.
+    Length(NumberRange),
+
+    /// 正则表达式, 没有 `^` `$` 时只需要包含匹配的部分.
+    Pattern(StringPattern),
+
+    /// 内置的格式, `string(email)`.
+    Format(StringFormat),
+}
+
+impl StringConstraint {
+    pub fn is_match(&self, s: &str) -> bool {
+        match self {
+            StringConstraint::Length(range) => range.contains(s.chars().count() as f64),
+            StringConstraint::Pattern(pattern) => pattern.regex.is_match(s),
+            StringConstraint::Format(format) => format.is_match(s),
+        }
+    }
+
+    /// 报错时使用, 例如: length `1..=64`, format `email`.
+    pub fn describe(&self) -> String {
+        match self {
+            StringConstraint::Length(range) => format!("length `{}`", range),
+            StringConstraint::Pattern(pattern) => format!("pattern `{}`", pattern.as_str()),
+            StringConstraint::Format(format) => format!("format `{}`", format.name()),
+        }
+    }
+}
+
+impl ToCbml for StringConstraint {
+    fn to_cbml(&self, _deepth: usize) -> String {
+        match self {
+            StringConstraint::Length(range) => range.to_string(),
+            StringConstraint::Pattern(pattern) => string_to_cbml(pattern.as_str()),
+            StringConstraint::Format(format) => format.name().to_string(),
+        }
+    }
+}
+
+/// `string("^[a-z]+$")` 中的正则表达式, 在解析 .def.cbml 时编译, 检查每一个值时不需要重新编译.
+/// 比较和 Debug 只看正则表达式的字符串.
+#[derive(Clone)]
+pub struct StringPattern {
+    regex: regex::Regex,
+}
+
+impl StringPattern {
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        return Ok(Self {
+            regex: regex::Regex::new(pattern)?,
+        });
+    }
+
+    pub fn as_str(&self) -> &str {
+        return self.regex.as_str();
+    }
+}
+
+impl PartialEq for StringPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl std::fmt::Debug for StringPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("StringPattern")
+            .field(&self.as_str())
+            .finish()
+    }
+}
+
+/// 数组的约束.  
+/// `[string](1..=10, unique)`  
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArrayConstraint {
+    /// 元素数量的范围, `[string](1..)` 表示至少有一个元素.
+    Length(NumberRange),
+
+    /// 元素不能重复.
+    Unique,
+}
+
+impl ToCbml for ArrayConstraint {
+    fn to_cbml(&self, _deepth: usize) -> String {
+        match self {
+            ArrayConstraint::Length(range) => range.to_string(),
+            ArrayConstraint::Unique => format!("unique"),
         }
     }
 }
@@ -1,5 +1,5 @@
 use crate::{AndThenTo, ToCbml};
-use std::collections::HashMap;
+use indexmap::IndexMap;
 
 /// cbml 的值.
 /// .cbml
@@ -12,14 +12,27 @@ pub enum CbmlValue {
     /// Literal `none`
     None,
 
+    /// Literal `todo`
+    /// 还没有填写的值.
+    Todo,
+
     /// [1,2,3]
     Array(Vec<CbmlValue>),
 
     /// person("张三")
     EnumField(String, Box<CbmlValue>),
 
+    /// 没有值的 enum 成员.
+    /// fast
+    EnumUnit(String),
+
     /// { name = "hello", age = 99 }
-    Struct(HashMap<String, CbmlValue>),
+    /// 字段保留书写的顺序.
+    Struct(IndexMap<String, CbmlValue>),
+
+    /// 类型是 `{ [string]: T }` 的值, 保留书写的顺序.
+    /// { HOME = "/root", LANG = "C" }
+    Map(Vec<(String, CbmlValue)>),
 }
 
 struct CbmlFile {
@@ -28,18 +41,28 @@ struct CbmlFile {
 
 impl ToCbml for CbmlValue {
     fn to_cbml(&self, deepth: usize) -> String {
+        self.to_cbml_with_indent(deepth, "    ")
+    }
+}
+
+impl CbmlValue {
+    /// 跟 to_cbml 相同, 但是使用 indent 作为每一层的缩进.
+    pub fn to_cbml_with_indent(&self, deepth: usize, indent: &str) -> String {
         match self {
             CbmlValue::None => format!("none"),
-            CbmlValue::String(s) => format!("\"{}\"", s),
+            CbmlValue::Todo => format!("todo"),
+            CbmlValue::String(s) => string_to_cbml(s),
             CbmlValue::Number(n) => format!("{}", n),
             CbmlValue::Boolean(b) => if *b { "true" } else { "false" }.to_string(),
             CbmlValue::Array(cbml_values) => {
                 let mut re = String::new();
                 re.push_str("[");
 
-                for l in cbml_values {
-                    re.push_str(&format!("{}, ", l.to_cbml(deepth)));
-                }
+                let elements: Vec<String> = cbml_values
+                    .iter()
+                    .map(|l| l.to_cbml_with_indent(deepth, indent))
+                    .collect();
+                re.push_str(&elements.join(", "));
 
                 re.push_str("]");
 
@@ -51,11 +74,12 @@ impl ToCbml for CbmlValue {
                     for l in cbml_values {
                         re.push_str(&format!(
                             "{}{},\n",
-                            "    ".repeat(deepth + 1),
-                            l.to_cbml(deepth + 1)
+                            indent.repeat(deepth + 1),
+                            l.to_cbml_with_indent(deepth + 1, indent)
                         ));
                     }
 
+                    re.push_str(&indent.repeat(deepth));
                     re.push_str("]");
                 }
                 return re;
@@ -67,12 +91,29 @@ impl ToCbml for CbmlValue {
                 for (name, val) in hash_map {
                     re.push_str(&format!(
                         "{}{} = {}\n",
-                        "    ".repeat(deepth + 1),
-                        name,
-                        val.to_cbml(deepth + 1)
+                        indent.repeat(deepth + 1),
+                        key_to_cbml(name),
+                        val.to_cbml_with_indent(deepth + 1, indent)
+                    ));
+                }
+                re.push_str(&indent.repeat(deepth));
+                re.push_str("}");
+
+                return re;
+            }
+            CbmlValue::Map(pairs) => {
+                let mut re = String::new();
+                re.push_str("{\n");
+
+                for (key, val) in pairs {
+                    re.push_str(&format!(
+                        "{}{} = {}\n",
+                        indent.repeat(deepth + 1),
+                        key_to_cbml(key),
+                        val.to_cbml_with_indent(deepth + 1, indent)
                     ));
                 }
-                re.push_str(&"    ".repeat(deepth));
+                re.push_str(&indent.repeat(deepth));
                 re.push_str("}");
 
                 return re;
@@ -80,13 +121,76 @@ impl ToCbml for CbmlValue {
             CbmlValue::EnumField(name, cbml_value) => {
                 let mut re = String::new();
 
-                re.push_str(&format!("{} (", name));
-                re.push_str(&cbml_value.to_cbml(0));
-                re.push_str(")\n");
+                re.push_str(&format!("{}(", name));
+                re.push_str(&cbml_value.to_cbml_with_indent(deepth, indent));
+                re.push_str(")");
                 return re;
             }
+            CbmlValue::EnumUnit(name) => name.clone(),
+        }
+    }
+}
+
+/// 可以不加双引号直接写出来的字段名和 map 的 key.
+/// 关键字在字段名的位置上也可以使用.
+pub fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    return chars
+        .next()
+        .is_some_and(|x| x.is_alphanumeric() && !x.is_ascii_digit())
+        && chars.all(|x| x.is_alphanumeric() || x == '_');
+}
+
+/// 输出字段名和 map 的 key, 不是标识符的 key 输出为字符串: `"serde-json" = "1.0"`.
+pub fn key_to_cbml(key: &str) -> String {
+    if is_identifier(key) {
+        return key.to_string();
+    }
+
+    return string_to_cbml(key);
+}
+
+/// 将字符串输出为 cbml 的字符串字面量.
+/// 如果字符串中有双引号, 则使用最短的不会冲突的 raw string 开始符号:
+/// say "hello" -> """say "hello""""
+/// 其他情况下输出为普通字符串, 并对 \\ 和控制字符做转义.
+pub fn string_to_cbml(s: &str) -> String {
+    // raw string 的内容不能以双引号开头.
+    if s.contains('"') && !s.starts_with('"') {
+        let mut longest = 0;
+        let mut count = 0;
+        for c in s.chars() {
+            if c == '"' {
+                count += 1;
+                longest = longest.max(count);
+            } else {
+                count = 0;
+            }
+        }
+
+        // raw string 至少需要 3 个双引号.
+        let quotes = "\"".repeat((longest + 1).max(3));
+        return format!("{}{}{}", quotes, s, quotes);
+    }
+
+    let mut re = String::with_capacity(s.len() + 2);
+    re.push('"');
+
+    for c in s.chars() {
+        match c {
+            '\\' => re.push_str("\\\\"),
+            '"' => re.push_str("\\\""),
+            '\n' => re.push_str("\\n"),
+            '\r' => re.push_str("\\r"),
+            '\t' => re.push_str("\\t"),
+            '\0' => re.push_str("\\0"),
+            c if c.is_control() => re.push_str(&format!("\\u{{{:X}}}", c as u32)),
+            c => re.push(c),
         }
     }
+
+    re.push('"');
+    return re;
 }
 
 impl CbmlValue {
@@ -145,13 +249,21 @@ impl<'a> AndThenTo<'a> for Option<&'a CbmlValue> {
         self.and_then(|x| x.cbml_array())
     }
 
-    fn cbml_struct(&self) -> Option<&'a HashMap<String, CbmlValue>> {
+    fn cbml_struct(&self) -> Option<&'a IndexMap<String, CbmlValue>> {
         self.and_then(|x| x.cbml_struct())
     }
 
+    fn cbml_map(&self) -> Option<&'a Vec<(String, CbmlValue)>> {
+        self.and_then(|x| x.cbml_map())
+    }
+
     fn cbml_enum_field(&self) -> Option<(String, Box<CbmlValue>)> {
         self.and_then(|x| x.cbml_enum_field())
     }
+
+    fn cbml_enum_unit(&self) -> Option<&'a str> {
+        self.and_then(|x| x.cbml_enum_unit())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -201,7 +313,7 @@ impl CbmlValue {
         return Some(vec);
     }
 
-    pub fn cbml_struct(&self) -> Option<&HashMap<String, CbmlValue>> {
+    pub fn cbml_struct(&self) -> Option<&IndexMap<String, CbmlValue>> {
         let CbmlValue::Struct(hash_map) = self else {
             return None;
         };
@@ -209,6 +321,14 @@ impl CbmlValue {
         return Some(hash_map);
     }
 
+    pub fn cbml_map(&self) -> Option<&Vec<(String, CbmlValue)>> {
+        let CbmlValue::Map(pairs) = self else {
+            return None;
+        };
+
+        return Some(pairs);
+    }
+
     // pub fn cbml_enum_field(&self) -> Result<(String, Box<CbmlValue>), ErrMsg> {
     pub fn cbml_enum_field(&self) -> Option<(String, Box<CbmlValue>)> {
         let CbmlValue::EnumField(name, cbml_value) = self else {
@@ -217,4 +337,214 @@ impl CbmlValue {
 
         return Some((name.clone(), cbml_value.clone()));
     }
+
+    pub fn cbml_enum_unit(&self) -> Option<&str> {
+        let CbmlValue::EnumUnit(name) = self else {
+            return None;
+        };
+
+        return Some(name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::token::TokenKind;
+    use crate::lexer::tokenize;
+    use crate::parser::StmtKind;
+    use crate::parser::cbml_parser::CbmlParser;
+    use crate::{ToCbml, ToCbmlValue};
+
+    #[test]
+    fn test_string_to_cbml() {
+        assert_eq!(string_to_cbml("hello"), r#""hello""#);
+        assert_eq!(string_to_cbml(r#"say "hi""#), "\"\"\"say \"hi\"\"\"\"");
+        assert_eq!(string_to_cbml(r#"a """ b"#), "\"\"\"\"a \"\"\" b\"\"\"\"");
+        assert_eq!(string_to_cbml(r#"C:\Users"#), r#""C:\\Users""#);
+        assert_eq!(string_to_cbml("\"start"), r#""\"start""#);
+        assert_eq!(string_to_cbml("a\nb\u{1}"), r#""a\nb\u{1}""#);
+
+        let cases = [r#"say "hi""#, r#"a """ b"#, r#"x"y""#, r#"x"y"""#, "a\n \"\" \n"];
+        for s in cases {
+            let code = string_to_cbml(s);
+            let tokens = tokenize("", &code).tokens;
+            assert_eq!(tokens.len(), 1, "{}", code);
+            assert_eq!(tokens[0].kind, TokenKind::String(s.to_string()));
+        }
+    }
+
+    /// 将 value 写成 `v = ...` 再解析回来.
+    fn parse_value(code: &str) -> CbmlValue {
+        let code = format!("v = {}", code);
+        let lexer_result = tokenize("", &code);
+        assert!(
+            lexer_result.errors.is_empty(),
+            "{:?}\n{}",
+            lexer_result.errors,
+            code
+        );
+
+        let mut parser = CbmlParser::new(String::new(), &lexer_result.tokens);
+        let re = parser.parse();
+        assert!(re.errors.is_empty(), "{:?}\n{}", re.errors, code);
+        assert_eq!(re.ast.len(), 1, "{}", code);
+
+        let StmtKind::Asignment(a) = &re.ast[0].kind else {
+            panic!("need asignment: {}", code);
+        };
+
+        // LiteralKind 的输出也需要能解析回来.
+        assert_eq!(
+            parse_literal_again(&a.value.to_cbml(0)),
+            a.value.to_cbml_value()
+        );
+
+        return a.value.to_cbml_value();
+    }
+
+    fn parse_literal_again(code: &str) -> CbmlValue {
+        let code = format!("v = {}", code);
+        let tokens = tokenize("", &code).tokens;
+        let re = CbmlParser::new(String::new(), &tokens).parse();
+        let StmtKind::Asignment(a) = &re.ast[0].kind else {
+            panic!("need asignment: {}", code);
+        };
+        return a.value.to_cbml_value();
+    }
+
+    /// xorshift, 测试用的随机数生成器.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: u64) -> usize {
+            (self.next() % n) as usize
+        }
+    }
+
+    fn gen_string(rng: &mut Rng) -> String {
+        const CHARS: [char; 20] = [
+            'a', 'z', 'A', '0', ' ', '"', '"', '\\', '\'', '\n', '\r', '\t', '\0', '\u{1}',
+            '\u{7f}', '{', '}', '你', '😀', '/',
+        ];
+
+        (0..rng.below(12)).map(|_| CHARS[rng.below(20)]).collect()
+    }
+
+    fn gen_number(rng: &mut Rng) -> f64 {
+        match rng.below(3) {
+            0 => rng.below(100000) as f64 - 50000.0,
+            1 => (rng.below(2000000) as f64 - 1000000.0) / 1000.0,
+            _ => loop {
+                // NaN 和 inf 无法用 cbml 表示.
+                let n = f64::from_bits(rng.next());
+                if n.is_finite() {
+                    break n;
+                }
+            },
+        }
+    }
+
+    fn gen_name(rng: &mut Rng) -> String {
+        const NAMES: [&str; 6] = ["name", "age", "a_1", "名字", "git", "Version"];
+        NAMES[rng.below(6)].to_string()
+    }
+
+    /// map 的 key 和结构体的字段名, 包含需要输出为字符串的 key.
+    fn gen_key(rng: &mut Rng) -> String {
+        const KEYS: [&str; 9] = [
+            "serde-json",
+            "_JAVA_OPTIONS",
+            "type",
+            "a b",
+            "",
+            "名字",
+            "x",
+            "say \"hi\"",
+            "1st",
+        ];
+        KEYS[rng.below(9)].to_string()
+    }
+
+    fn gen_value(rng: &mut Rng, deepth: usize) -> CbmlValue {
+        let kind = if deepth > 3 {
+            rng.below(4)
+        } else {
+            rng.below(8)
+        };
+
+        match kind {
+            0 => CbmlValue::String(gen_string(rng)),
+            1 => CbmlValue::Number(gen_number(rng)),
+            2 => CbmlValue::Boolean(rng.below(2) == 0),
+            3 => match rng.below(2) {
+                0 => CbmlValue::None,
+                _ => CbmlValue::Todo,
+            },
+            4 => CbmlValue::Array(
+                (0..rng.below(4))
+                    .map(|_| gen_value(rng, deepth + 1))
+                    .collect(),
+            ),
+            5 => match rng.below(2) {
+                0 => CbmlValue::EnumField(gen_name(rng), Box::new(gen_value(rng, deepth + 1))),
+                _ => CbmlValue::EnumUnit(gen_name(rng)),
+            },
+            6 => {
+                let mut fields = IndexMap::new();
+                for _ in 0..rng.below(4) {
+                    fields.insert(gen_name(rng), gen_value(rng, deepth + 1));
+                }
+                CbmlValue::Struct(fields)
+            }
+            _ => {
+                let mut pairs: IndexMap<String, CbmlValue> = IndexMap::new();
+                for _ in 0..rng.below(4) {
+                    pairs.insert(gen_key(rng), gen_value(rng, deepth + 1));
+                }
+                CbmlValue::Map(pairs.into_iter().collect())
+            }
+        }
+    }
+
+    /// 没有类型定义时 `{ ... }` 会解析为 Struct, 所以 Map 需要转换为 Struct 再比较.
+    fn map_to_struct(v: &CbmlValue) -> CbmlValue {
+        match v {
+            CbmlValue::Array(x) => CbmlValue::Array(x.iter().map(map_to_struct).collect()),
+            CbmlValue::EnumField(name, x) => {
+                CbmlValue::EnumField(name.clone(), Box::new(map_to_struct(x)))
+            }
+            CbmlValue::Struct(fields) => CbmlValue::Struct(
+                fields
+                    .iter()
+                    .map(|(k, x)| (k.clone(), map_to_struct(x)))
+                    .collect(),
+            ),
+            CbmlValue::Map(pairs) => CbmlValue::Struct(
+                pairs
+                    .iter()
+                    .map(|(k, x)| (k.clone(), map_to_struct(x)))
+                    .collect(),
+            ),
+            x => x.clone(),
+        }
+    }
+
+    #[test]
+    fn test_to_cbml_round_trip() {
+        let mut rng = Rng(0x2545F4914F6CDD1D);
+
+        for _ in 0..2000 {
+            let v = gen_value(&mut rng, 0);
+            let code = v.to_cbml(0);
+            assert_eq!(parse_value(&code), map_to_struct(&v), "\n{}", code);
+        }
+    }
 }
@@ -3,3 +3,4 @@
 
 pub mod cbml_type;
 pub mod cbml_value;
+pub mod string_format;
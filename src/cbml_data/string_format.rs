@@ -0,0 +1,175 @@
+//! string 的内置格式: `string(email)` `string(semver)` ...
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StringFormat {
+    /// user@example.com
+    Email,
+    /// https://example.com/a?b=c
+    Uri,
+    /// 127.0.0.1
+    Ipv4,
+    /// ::1
+    Ipv6,
+    /// 1.2.3-alpha.1+build.5
+    Semver,
+    /// www.example.com
+    Hostname,
+}
+
+impl StringFormat {
+    pub const ALL: [StringFormat; 6] = [
+        StringFormat::Email,
+        StringFormat::Uri,
+        StringFormat::Ipv4,
+        StringFormat::Ipv6,
+        StringFormat::Semver,
+        StringFormat::Hostname,
+    ];
+
+    /// 在 .def.cbml 中的名字.
+    pub fn name(&self) -> &'static str {
+        match self {
+            StringFormat::Email => "email",
+            StringFormat::Uri => "uri",
+            StringFormat::Ipv4 => "ipv4",
+            StringFormat::Ipv6 => "ipv6",
+            StringFormat::Semver => "semver",
+            StringFormat::Hostname => "hostname",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().find(|x| x.name() == name).copied()
+    }
+
+    pub fn is_match(&self, s: &str) -> bool {
+        match self {
+            StringFormat::Email => is_email(s),
+            StringFormat::Uri => is_uri(s),
+            StringFormat::Ipv4 => s.parse::<Ipv4Addr>().is_ok(),
+            StringFormat::Ipv6 => s.parse::<Ipv6Addr>().is_ok(),
+            StringFormat::Semver => is_semver(s),
+            StringFormat::Hostname => is_hostname(s),
+        }
+    }
+}
+
+/// local@domain, domain 需要是包含 `.` 的 hostname.
+fn is_email(s: &str) -> bool {
+    let Some((local, domain)) = s.split_once('@') else {
+        return false;
+    };
+
+    let local_is_valid = !local.is_empty()
+        && !local.contains('@')
+        && !local.chars().any(|x| x.is_whitespace() || x.is_control());
+
+    return local_is_valid && domain.contains('.') && is_hostname(domain);
+}
+
+/// scheme:rest, scheme 以字母开头, 后面是字母 数字 `+` `-` `.`.
+fn is_uri(s: &str) -> bool {
+    let Some((scheme, rest)) = s.split_once(':') else {
+        return false;
+    };
+
+    let mut chars = scheme.chars();
+    let scheme_is_valid = chars.next().is_some_and(|x| x.is_ascii_alphabetic())
+        && chars.all(|x| x.is_ascii_alphanumeric() || matches!(x, '+' | '-' | '.'));
+
+    return scheme_is_valid
+        && !rest.is_empty()
+        && !rest.chars().any(|x| x.is_whitespace() || x.is_control());
+}
+
+/// 每一段 1 到 63 个字符, 只能是字母 数字 `-`, 不能以 `-` 开头或结尾, 总长度不超过 253.
+fn is_hostname(s: &str) -> bool {
+    if s.is_empty() || s.len() > 253 {
+        return false;
+    }
+
+    return s.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|x| x.is_ascii_alphanumeric() || x == '-')
+    });
+}
+
+/// MAJOR.MINOR.PATCH[-PRERELEASE][+BUILD], https://semver.org
+fn is_semver(s: &str) -> bool {
+    let (rest, build) = match s.split_once('+') {
+        Some((rest, build)) => (rest, Some(build)),
+        None => (s, None),
+    };
+    let (core, pre) = match rest.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (rest, None),
+    };
+
+    let core: Vec<&str> = core.split('.').collect();
+    if core.len() != 3 || !core.iter().all(|x| is_semver_number(x)) {
+        return false;
+    }
+
+    if let Some(pre) = pre {
+        let is_valid = pre.split('.').all(|x| {
+            is_semver_identifier(x)
+                && (!x.chars().all(|c| c.is_ascii_digit()) || is_semver_number(x))
+        });
+        if !is_valid {
+            return false;
+        }
+    }
+
+    if let Some(build) = build {
+        if !build.split('.').all(is_semver_identifier) {
+            return false;
+        }
+    }
+
+    return true;
+}
+
+/// 数字, 除了 0 之外不能以 0 开头.
+fn is_semver_number(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|x| x.is_ascii_digit()) && (s == "0" || !s.starts_with('0'))
+}
+
+fn is_semver_identifier(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|x| x.is_ascii_alphanumeric() || x == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_format() {
+        let cases = [
+            (StringFormat::Email, "a.b@example.com", true),
+            (StringFormat::Email, "a@localhost", false),
+            (StringFormat::Email, "a b@example.com", false),
+            (StringFormat::Uri, "https://example.com/a?b=c", true),
+            (StringFormat::Uri, "mailto:a@example.com", true),
+            (StringFormat::Uri, "example.com", false),
+            (StringFormat::Ipv4, "127.0.0.1", true),
+            (StringFormat::Ipv4, "256.0.0.1", false),
+            (StringFormat::Ipv6, "::1", true),
+            (StringFormat::Ipv6, "127.0.0.1", false),
+            (StringFormat::Semver, "1.2.3-alpha.1+build.5", true),
+            (StringFormat::Semver, "1.0", false),
+            (StringFormat::Semver, "01.2.3", false),
+            (StringFormat::Semver, "1.2.3-01", false),
+            (StringFormat::Hostname, "www.example.com", true),
+            (StringFormat::Hostname, "-a.com", false),
+            (StringFormat::Hostname, "a..com", false),
+        ];
+
+        for (format, s, expected) in cases {
+            assert_eq!(format.is_match(s), expected, "{} {:?}", format.name(), s);
+        }
+    }
+}
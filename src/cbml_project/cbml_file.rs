@@ -2,17 +2,19 @@ use super::def_cbml_file::DefCbmlFile;
 use super::types::FieldAsign;
 use super::types::FieldDef;
 use super::types::ScopeID;
+use super::types::ScopeSegment;
 use super::types::TypeInfo;
 
 use crate::ToCbml;
 use crate::ToCbmlValue;
-use crate::cbml_data::cbml_type::CbmlType;
+use crate::cbml_data::cbml_type::{ArrayConstraint, CbmlType};
 use crate::cbml_data::cbml_value::CbmlValue;
 use crate::lexer::token::*;
 use crate::lexer::tokenize;
 use crate::parser::CbmlParser;
 use crate::parser::ast::stmt::*;
 use crate::parser::parser_error::CbmlError;
+use indexmap::IndexMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 
@@ -30,12 +32,15 @@ pub struct CbmlFile {
     /// 检查到的错误.
     pub errors: Vec<CbmlError>,
 
+    /// 检查到的警告, 例如还没有填写的 todo.
+    pub warnings: Vec<CbmlError>,
+
     /// fn check_unasigned_field(&mut self) 会用到这个属性.
     last_line_span: Span,
 
     field_id: usize,
     /// 解析 ast 时记录正在解析的语句所在的 scope.
-    _current_scope: Vec<ScopeID>,
+    _current_scope: ScopeID,
 }
 
 impl CbmlFile {
@@ -47,9 +52,10 @@ impl CbmlFile {
             // use_url: None,
             typedef_file: None,
             errors: Vec::new(),
+            warnings: Vec::new(),
             last_line_span: Span::empty(),
             field_id: 0,
-            _current_scope: Vec::new(),
+            _current_scope: ScopeID::empty(),
         };
 
         if (&file_path).ends_with(".def.cbml") {
@@ -80,9 +86,10 @@ impl CbmlFile {
             // use_url: None,
             typedef_file: None,
             errors: Vec::new(),
+            warnings: Vec::new(),
             last_line_span: Span::empty(),
             field_id: 0,
-            _current_scope: Vec::new(),
+            _current_scope: ScopeID::empty(),
         };
 
         if (&file_path).ends_with(".def.cbml") {
@@ -105,6 +112,137 @@ impl CbmlFile {
         return f;
     }
 
+    /// 使用已经解析好的类型定义文件, code 中不需要再写 use 语句.
+    pub fn new_from_with_typedef(file_path: String, code: &str, typedef_file: DefCbmlFile) -> Self {
+        let mut f = Self {
+            file_path: file_path.clone(),
+            fields: Vec::new(),
+            typedef_file: None,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            last_line_span: Span::empty(),
+            field_id: 0,
+            _current_scope: ScopeID::empty(),
+        };
+
+        if !typedef_file.errors.is_empty() {
+            let e = CbmlError {
+                error_code: 0000,
+                file_path: file_path.clone(),
+                msg: format!(
+                    "类型定义文件中有 {} 个错误: \n{}",
+                    typedef_file.errors.len(),
+                    typedef_file.file_path
+                ),
+                span: Span::empty(),
+                note: None,
+                help: None,
+            };
+            f.errors.push(e);
+        }
+
+        f.typedef_file = Some(typedef_file);
+        f.parse_code(code);
+
+        f.error_check();
+
+        return f;
+    }
+
+    /// 找到所有还是 todo 的值.
+    /// return: (字段路径, todo 的位置), 字段路径例如 `a.b[0].c`.
+    pub fn get_todo_fields(&self) -> Vec<(String, Span)> {
+        let mut re: Vec<(String, Span)> = vec![];
+
+        let root_id = ScopeID::empty();
+        for x in self.fields.iter().filter(|x| x.scope == root_id) {
+            Self::find_todo(&x.value, x.child_scope(), &mut re);
+        }
+
+        return re;
+    }
+
+    /// scope: literal 所在的位置, 也就是 literal 的路径.
+    fn find_todo(literal: &Literal, scope: ScopeID, re: &mut Vec<(String, Span)>) {
+        match &literal.kind {
+            LiteralKind::Todo => re.push((scope.to_string(), literal.span.clone())),
+            LiteralKind::Array(literals) => {
+                for (i, x) in literals.iter().enumerate() {
+                    Self::find_todo(x, scope.index(i), re);
+                }
+            }
+            LiteralKind::Struct(asignment_stmts) => {
+                for x in asignment_stmts {
+                    Self::find_todo(&x.value, scope.child(&x.field_name), re);
+                }
+            }
+            LiteralKind::EnumFieldLiteral {
+                field_name,
+                literal: Some(literal),
+                ..
+            } => {
+                Self::find_todo(literal, scope.variant(field_name), re);
+            }
+            _ => {}
+        }
+    }
+
+    /// 每一个值在代码中的位置, key 是值的路径, 例如 `a.b[0].c`.
+    /// 省略了的字段使用的默认值不在代码中, 所以没有位置.
+    pub fn literal_spans(&self) -> HashMap<ScopeID, Span> {
+        let mut re: HashMap<ScopeID, Span> = HashMap::new();
+
+        let root_id = ScopeID::empty();
+        for x in self.fields.iter().filter(|x| x.scope == root_id) {
+            Self::find_literal_spans(&x.value, x.child_scope(), &mut re);
+        }
+
+        return re;
+    }
+
+    fn find_literal_spans(literal: &Literal, scope: ScopeID, re: &mut HashMap<ScopeID, Span>) {
+        match &literal.kind {
+            LiteralKind::Array(literals) => {
+                for (i, x) in literals.iter().enumerate() {
+                    Self::find_literal_spans(x, scope.index(i), re);
+                }
+            }
+            LiteralKind::Struct(asignment_stmts) => {
+                for x in asignment_stmts {
+                    Self::find_literal_spans(&x.value, scope.child(&x.field_name), re);
+                }
+            }
+            LiteralKind::EnumFieldLiteral {
+                field_name,
+                literal: Some(literal),
+                ..
+            } => {
+                Self::find_literal_spans(literal, scope.variant(field_name), re);
+            }
+            _ => {}
+        }
+
+        re.insert(scope, literal.span.clone());
+    }
+
+    /// 跟 to_cbml_value() 相同, 但是有错误时返回这些错误,
+    /// 没有错误但是还有 todo 没有填写时返回错误: field `a.b` is still todo.
+    pub fn try_to_cbml_value(&self) -> Result<CbmlValue, Vec<CbmlError>> {
+        if !self.errors.is_empty() {
+            return Err(self.errors.clone());
+        }
+
+        if let Some((path, span)) = self.get_todo_fields().into_iter().next() {
+            return Err(vec![CbmlError::err_field_is_todo(
+                self.file_path.clone(),
+                span,
+                &path,
+            )]);
+        }
+
+        return Ok(self.to_cbml_value());
+    }
+
     pub fn get_all_errors(&self) -> Vec<CbmlError> {
         let mut re: Vec<CbmlError> = vec![];
 
@@ -119,7 +257,7 @@ impl CbmlFile {
             return None;
         };
 
-        let asdf = def_file.fields_map.get(&(field_name.clone(), scope));
+        let asdf = def_file.get_field_def_by_name(field_name.clone(), scope);
 
         return asdf;
     }
@@ -141,13 +279,20 @@ impl CbmlFile {
 
         let mut unasigned_fields: Vec<&FieldDef> = Vec::new();
 
-        let mut asigned_fields = self.fields.iter().map(|x| &x.name).collect::<HashSet<_>>();
+        let top_scope = ScopeID::empty();
+        let mut asigned_fields = self
+            .fields
+            .iter()
+            .filter(|x| x.scope == top_scope)
+            .map(|x| &x.name)
+            .collect::<HashSet<_>>();
 
         // 找出定义了却没有赋值的 top level 字段,
+        // 可选类型和有默认值的字段可以不赋值.
         {
             for x in def_file.get_all_top_fields() {
                 let remoed = asigned_fields.remove(&x.name);
-                if !remoed {
+                if !remoed && !x.can_be_omitted() {
                     unasigned_fields.push(x);
                 }
             }
@@ -156,6 +301,114 @@ impl CbmlFile {
         return unasigned_fields;
     }
 
+    /// 获取所有层级中定义了却并未赋值的字段, 包括数组中的结构体和枚举的值.
+    /// return: (结构体字面量的位置, 未赋值字段的完整路径), 路径例如 `a.b[0].c`.
+    pub fn get_missing_fields(&self) -> Vec<(Span, Vec<String>)> {
+        let mut re: Vec<(Span, Vec<String>)> = Vec::new();
+
+        let top_missing: Vec<String> = self
+            .get_unasigned_fields()
+            .iter()
+            .map(|x| x.name.clone())
+            .collect();
+        if !top_missing.is_empty() {
+            re.push((self.last_line_span.clone(), top_missing));
+        }
+
+        let top_scope = ScopeID::empty();
+        for x in self.fields.iter().filter(|x| x.scope == top_scope) {
+            let Some(type_info) = self.get_field_defined_type(x) else {
+                continue;
+            };
+
+            self.find_missing_fields(&type_info.ty, &x.value, &x.child_scope(), &mut re);
+        }
+
+        return re;
+    }
+
+    /// scope: literal 中的子字段所在的 scope, 用来查找子字段的定义, 也是 literal 的路径.
+    fn find_missing_fields(
+        &self,
+        need_type: &CbmlType,
+        literal: &Literal,
+        scope: &ScopeID,
+        re: &mut Vec<(Span, Vec<String>)>,
+    ) {
+        match (need_type, &literal.kind) {
+            (CbmlType::Struct { fields }, LiteralKind::Struct(asignment_stmts)) => {
+                // 没有赋值的字段需要是可选类型或者有默认值.
+                let missing: Vec<String> = fields
+                    .iter()
+                    .filter(|f| {
+                        !asignment_stmts.iter().any(|x| x.field_name == f.0)
+                            && !self
+                                .get_field_def(&f.0, scope.clone())
+                                .is_some_and(|x| x.can_be_omitted())
+                    })
+                    .map(|f| scope.child(&f.0).to_string())
+                    .collect();
+
+                if !missing.is_empty() {
+                    re.push((literal.span.clone(), missing));
+                }
+
+                for x in asignment_stmts {
+                    let Some(field) = fields.iter().find(|f| f.0 == x.field_name) else {
+                        continue;
+                    };
+
+                    self.find_missing_fields(&field.1, &x.value, &scope.child(&x.field_name), re);
+                }
+            }
+            (CbmlType::Array { inner_type, .. }, LiteralKind::Array(literals)) => {
+                for (i, x) in literals.iter().enumerate() {
+                    self.find_missing_fields(inner_type, x, &scope.index(i), re);
+                }
+            }
+            (CbmlType::Map { value_type }, LiteralKind::Struct(asignment_stmts)) => {
+                for x in asignment_stmts {
+                    self.find_missing_fields(value_type, &x.value, &scope.child(&x.field_name), re);
+                }
+            }
+            (CbmlType::Optional { inner_type }, _) => {
+                self.find_missing_fields(inner_type, literal, scope, re);
+            }
+            (CbmlType::Named { type_name }, _) => {
+                let Some(def_file) = &self.typedef_file else {
+                    return;
+                };
+                let Some(info) = def_file.get_type_by_name(type_name) else {
+                    return;
+                };
+
+                self.find_missing_fields(&info.ty, literal, scope, re);
+            }
+            (
+                CbmlType::Enum { fields },
+                LiteralKind::EnumFieldLiteral {
+                    field_name,
+                    literal: Some(literal),
+                    ..
+                },
+            ) => {
+                let Some((_, Some(field_type))) = fields.iter().find(|f| &f.0 == field_name) else {
+                    return;
+                };
+
+                self.find_missing_fields(field_type, literal, &scope.variant(field_name), re);
+            }
+            (CbmlType::TypeUnion { types }, _) => {
+                // 只检查完全符合的那个类型, 没有符合的类型时在 check_literal_type 中报错.
+                let (matched, errors) = self.closest_alternative(types, literal, scope);
+                if errors.is_empty() {
+                    self.find_missing_fields(matched, literal, scope, re);
+                }
+            }
+            _ => {}
+        }
+    }
+
     /// goto_difinition 的时候会用到.
     pub fn get_field_def_by_location(&self, line: u32, colunm: u32) -> Vec<&FieldDef> {
         let mut matchd_field_asign: Vec<&FieldAsign> = Vec::new();
@@ -177,28 +430,159 @@ impl CbmlFile {
     }
 
     fn kind_to_value(&self, f: FieldAsign) -> CbmlValue {
-        match f.value.kind {
-            LiteralKind::String(s) => CbmlValue::String(s),
-            LiteralKind::Number(n) => CbmlValue::Number(n),
-            LiteralKind::Boolean(b) => CbmlValue::Boolean(b),
-            LiteralKind::Array(literals) => {
-                CbmlValue::Array(literals.iter().map(|x| x.to_cbml_value()).collect())
+        let field_def = self.get_field_def(&f.name, f.scope.clone());
+        let need_type = field_def.map(|x| &x.type_.ty);
+
+        return self.literal_to_value(&f.value, field_def, need_type, f.child_scope());
+    }
+
+    /// 没有赋值的字段使用默认值, 可选类型的字段使用 none.
+    /// scope: 这些字段所在的 scope.
+    fn fill_omitted_fields(&self, fields: &mut IndexMap<String, CbmlValue>, scope: &ScopeID) {
+        let Some(def_file) = &self.typedef_file else {
+            return;
+        };
+
+        // `source: Git | Dir` 时 scope 对应多个类型, 使用包含所有已赋值字段的那一个类型.
+        let Some(def_scope) = def_file.resolve_scopes(scope).into_iter().find(|s| {
+            fields
+                .keys()
+                .all(|k| def_file.fields_map.contains_key(&(k.clone(), s.clone())))
+        }) else {
+            return;
+        };
+
+        for x in def_file.get_fields_by_scope(&def_scope) {
+            if fields.contains_key(&x.name) {
+                continue;
+            }
+
+            if let Some(default_value) = &x.default_value {
+                fields.insert(x.name.clone(), default_value.to_cbml_value());
+            } else if x.can_be_omitted() {
+                fields.insert(x.name.clone(), CbmlValue::None);
             }
+        }
+    }
+
+    /// `default` 会被替换为字段定义中的默认值.
+    /// field_def: 这个字面量所赋值的字段的定义.
+    /// need_type: 这个字面量的类型, 类型是 map 的结构体字面量会转换为 CbmlValue::Map.
+    /// child_scope: 这个字面量中的子字段所在的 scope.
+    fn literal_to_value(
+        &self,
+        literal: &Literal,
+        field_def: Option<&FieldDef>,
+        need_type: Option<&CbmlType>,
+        child_scope: ScopeID,
+    ) -> CbmlValue {
+        let need_type = need_type.and_then(|x| self.concrete_type(x, literal, &child_scope));
+
+        match &literal.kind {
+            LiteralKind::Default => {
+                let Some(default_value) = field_def.and_then(|x| x.default_value.as_ref()) else {
+                    return CbmlValue::None;
+                };
+
+                return default_value.to_cbml_value();
+            }
+            LiteralKind::Array(literals) => CbmlValue::Array(
+                literals
+                    .iter()
+                    .enumerate()
+                    .map(|(i, x)| {
+                        let inner_type = match need_type {
+                            Some(CbmlType::Array { inner_type, .. }) => Some(inner_type.as_ref()),
+                            _ => None,
+                        };
+
+                        self.literal_to_value(x, None, inner_type, child_scope.index(i))
+                    })
+                    .collect(),
+            ),
             LiteralKind::Struct(asignment_stmts) => {
-                let mut fields: HashMap<String, CbmlValue> = HashMap::new();
+                if let Some(CbmlType::Map { value_type }) = need_type {
+                    // map 保留书写的顺序, 也没有需要填充的字段.
+                    let pairs: Vec<(String, CbmlValue)> = asignment_stmts
+                        .iter()
+                        .map(|x| {
+                            let scope = child_scope.child(&x.field_name);
+                            let value =
+                                self.literal_to_value(&x.value, None, Some(value_type), scope);
+
+                            (x.field_name.clone(), value)
+                        })
+                        .collect();
+
+                    return CbmlValue::Map(pairs);
+                }
+
+                let mut fields: IndexMap<String, CbmlValue> = IndexMap::new();
 
                 for x in asignment_stmts {
-                    fields.insert(x.field_name.clone(), x.value.to_cbml_value());
+                    let def = self.get_field_def(&x.field_name, child_scope.clone());
+                    let field_type = def.map(|x| &x.type_.ty);
+                    let scope = child_scope.child(&x.field_name);
+
+                    fields.insert(
+                        x.field_name.clone(),
+                        self.literal_to_value(&x.value, def, field_type, scope),
+                    );
                 }
 
+                self.fill_omitted_fields(&mut fields, &child_scope);
+
                 return CbmlValue::Struct(fields);
             }
-            LiteralKind::LiteralNone => CbmlValue::None,
             LiteralKind::EnumFieldLiteral {
                 field_name,
                 literal,
                 ..
-            } => CbmlValue::EnumField(field_name, Box::new(literal.to_cbml_value())),
+            } => {
+                let Some(literal) = literal else {
+                    return CbmlValue::EnumUnit(field_name.clone());
+                };
+
+                let scope = child_scope.variant(field_name);
+                let field_type = match need_type {
+                    Some(CbmlType::Enum { fields }) => fields
+                        .iter()
+                        .find(|x| &x.0 == field_name)
+                        .and_then(|x| x.1.as_ref()),
+                    _ => None,
+                };
+
+                CbmlValue::EnumField(
+                    field_name.clone(),
+                    Box::new(self.literal_to_value(literal, None, field_type, scope)),
+                )
+            }
+            _ => literal.to_cbml_value(),
+        }
+    }
+
+    /// 去掉 `?T` 和具名类型, `A | B` 使用跟 literal 完全符合的那一个类型.
+    /// 没有符合的类型时返回 None.
+    fn concrete_type<'a>(
+        &'a self,
+        need_type: &'a CbmlType,
+        literal: &Literal,
+        scope: &ScopeID,
+    ) -> Option<&'a CbmlType> {
+        match need_type {
+            CbmlType::Optional { inner_type } => self.concrete_type(inner_type, literal, scope),
+            CbmlType::Named { type_name } => {
+                let info = self.typedef_file.as_ref()?.get_type_by_name(type_name)?;
+                return self.concrete_type(&info.ty, literal, scope);
+            }
+            CbmlType::TypeUnion { types } => {
+                let (matched, errors) = self.closest_alternative(types, literal, scope);
+                if !errors.is_empty() {
+                    return None;
+                }
+                return self.concrete_type(matched, literal, scope);
+            }
+            _ => Some(need_type),
         }
     }
 }
@@ -278,9 +662,9 @@ impl CbmlFile {
             crate::parser::StmtKind::FileFieldStmt(struct_field_def_stmt) => {
                 self.parse_struct_field_def(&struct_field_def_stmt);
             }
-            crate::parser::StmtKind::TypeAliasStmt(_) => {
-                todo!()
-            }
+            crate::parser::StmtKind::TypeAliasStmt(type_alias_stmt) => self.parse_type_def(
+                &crate::parser::ast::stmt::TypeDefStmt::TypeAliasStmt(type_alias_stmt),
+            ),
             crate::parser::StmtKind::StructDefStmt(struct_def) => {
                 self.parse_struct_def(&struct_def)
             }
@@ -348,7 +732,7 @@ impl CbmlFile {
 
         self.fields.push(value);
 
-        self.into_scope(ScopeID::new(asignment_stmt.field_name.clone()));
+        self.into_scope(ScopeSegment::Field(asignment_stmt.field_name.clone()));
         self.parse_chile_fields(
             &asignment_stmt.value.kind,
             asignment_stmt.field_name.clone(),
@@ -358,6 +742,34 @@ impl CbmlFile {
 
     fn parse_chile_fields(&mut self, kind: &LiteralKind, field_name: String) {
         match kind {
+            // default 只能直接作为字段的值使用.
+            LiteralKind::Array(literals)
+                if literals.iter().any(|x| x.kind == LiteralKind::Default) =>
+            {
+                for (i, x) in literals.iter().enumerate() {
+                    if x.kind == LiteralKind::Default {
+                        self.errors
+                            .push(CbmlError::err_default_keyword_not_allowed_in_literal(
+                                self.file_path.clone(),
+                                x.span.clone(),
+                            ));
+                    } else {
+                        self.into_scope(ScopeSegment::Index(i));
+                        self.parse_chile_fields(&x.kind, field_name.clone());
+                        self.outgoing_scope();
+                    }
+                }
+            }
+            LiteralKind::EnumFieldLiteral {
+                literal: Some(literal),
+                ..
+            } if literal.kind == LiteralKind::Default => {
+                self.errors
+                    .push(CbmlError::err_default_keyword_not_allowed_in_literal(
+                        self.file_path.clone(),
+                        literal.span.clone(),
+                    ));
+            }
             // LiteralKind::Array(literals) => todo!(),
             LiteralKind::Struct(asignment_stmts) => {
                 for x in asignment_stmts {
@@ -366,16 +778,19 @@ impl CbmlFile {
             }
             LiteralKind::EnumFieldLiteral {
                 field_name: enum_field_name,
-                literal,
+                literal: Some(literal),
                 ..
             } => {
-                self.into_scope(ScopeID::new(enum_field_name.clone()));
+                self.into_scope(ScopeSegment::Variant(enum_field_name.clone()));
                 self.parse_chile_fields(&literal.kind, enum_field_name.clone());
                 self.outgoing_scope();
             }
             LiteralKind::Array(literals) => {
-                for x in literals {
+                // 数组中的每一个元素都有自己的 scope.
+                for (i, x) in literals.iter().enumerate() {
+                    self.into_scope(ScopeSegment::Index(i));
                     self.parse_chile_fields(&x.kind, field_name.clone());
+                    self.outgoing_scope();
                 }
             }
 
@@ -463,18 +878,11 @@ impl CbmlFile {
     }
 
     fn get_current_scope_id(&self) -> ScopeID {
-        let mut re = String::new();
-
-        for x in &self._current_scope {
-            re.push_str("::");
-            re.push_str(&x.0);
-        }
-
-        return ScopeID::new(re);
+        return self._current_scope.clone();
     }
 
-    fn into_scope(&mut self, scope_id: ScopeID) {
-        self._current_scope.push(scope_id);
+    fn into_scope(&mut self, segment: ScopeSegment) {
+        self._current_scope.push(segment);
     }
 
     fn outgoing_scope(&mut self) {
@@ -518,6 +926,7 @@ impl CbmlFile {
 
     fn error_check(&mut self) {
         self.check_duplicated_field_name();
+        self.check_todo();
         if let Some(def_file) = &self.typedef_file {
             if !def_file.errors.is_empty() {
                 return;
@@ -528,216 +937,504 @@ impl CbmlFile {
 
         self.check_unasigned_field();
         self.check_type();
-
-        // self.check_extra_field_asign();
+        self.check_extra_field_asign();
     }
 
     // 类型检查, 检查赋值的类型跟定义的字段的类型是否相同.
+    // 只需要检查 top level field, 子字段在 check_literal_type 中逐层检查.
     fn check_type(&mut self) {
-        for x in &self.fields {
-            let re = self.check_one_field_type(x);
-            match re {
-                Ok(_) => {}
-                Err(e) => {
-                    self.errors.push(e);
-                }
+        let top_scope = ScopeID::empty();
+        let mut errors: Vec<CbmlError> = Vec::new();
+
+        for x in self.fields.iter().filter(|x| x.scope == top_scope) {
+            errors.extend(self.check_one_field_type(x));
+        }
+
+        self.errors.extend(errors);
+    }
+
+    /// 检查 literal 最外层的形状是否跟 need_type 相同, 不检查子字段.
+    fn is_same_shape(&self, need_type: &CbmlType, found: &Literal) -> bool {
+        let kind = &found.kind;
+        // default 在 check_default_literal 中检查.
+        if let LiteralKind::Default = kind {
+            return true;
+        }
+        // todo 可以是任何类型, 在 check_todo 中作为警告.
+        if let LiteralKind::Todo = kind {
+            return true;
+        }
+
+        match need_type {
+            CbmlType::String { .. } => matches!(kind, LiteralKind::String { .. }),
+            CbmlType::Number { .. } => matches!(kind, LiteralKind::Number(_)),
+            // 范围在 check_literal_type 中检查.
+            CbmlType::Int { .. } => matches!(kind, LiteralKind::Number(n) if n.fract() == 0.0),
+            CbmlType::Bool => matches!(kind, LiteralKind::Boolean(_)),
+            CbmlType::Any => true,
+            CbmlType::Array { .. } => matches!(kind, LiteralKind::Array(_)),
+            CbmlType::Struct { .. } => matches!(kind, LiteralKind::Struct(_)),
+            CbmlType::Map { .. } => matches!(kind, LiteralKind::Struct(_)),
+            CbmlType::Optional { inner_type } => match kind {
+                LiteralKind::LiteralNone => true,
+                _ => self.is_same_shape(inner_type, found),
+            },
+            CbmlType::Union { allowed_values } => allowed_values.contains(&found.to_cbml_value()),
+            CbmlType::Named { type_name } => {
+                // 递归类型, 字面量是有限的, 所以按需展开就不会无限递归.
+                let Some(def_file) = &self.typedef_file else {
+                    return false;
+                };
+                let Some(info) = def_file.get_type_by_name(type_name) else {
+                    return false;
+                };
+
+                return self.is_same_shape(&info.ty, found);
             }
+            CbmlType::TypeUnion { types } => types.iter().any(|x| self.is_same_shape(x, found)),
+            // EnumFieldLiteral 的名字是否包含在 CbmlType::Enum fields 中在 check_literal_type 中检查.
+            CbmlType::Enum { .. } => matches!(kind, LiteralKind::EnumFieldLiteral { .. }),
         }
     }
 
-    #[allow(dead_code)]
-    fn type_check_for_array(
-        &mut self,
-        inner_type: Box<CbmlType>,
-        found: &LiteralKind,
-        span: Span,
-    ) -> Result<(), CbmlError> {
-        //
-        match found {
-            LiteralKind::Array(literals) => {
-                literals.iter().all(|x| self.is_same_type(&inner_type, x));
+    /// 逐层检查 found 的类型, 每一个类型不对的节点返回一个错误.
+    /// scope: found 中的子字段所在的 scope, 用来查找子字段的定义,
+    /// 也是 found 的完整路径, 例如 `tree.obj.obj.age`.
+    fn check_literal_type(
+        &self,
+        need_type: &CbmlType,
+        found: &Literal,
+        scope: &ScopeID,
+    ) -> Vec<CbmlError> {
+        let mut re: Vec<CbmlError> = Vec::new();
+
+        if !self.is_same_shape(need_type, found) {
+            let mut e = CbmlError::err_mismatched_types(
+                self.file_path.clone(),
+                found.span.clone(),
+                &scope.to_string(),
+                &need_type.to_cbml(0),
+                &found.kind.to_cbml(0),
+            );
+            e.help = self.union_values_help(need_type);
+            re.push(e);
+            return re;
+        }
+
+        match (need_type, &found.kind) {
+            (
+                CbmlType::Number { range: Some(range) } | CbmlType::Int { range: Some(range) },
+                LiteralKind::Number(n),
+            ) => {
+                if !range.contains(*n) {
+                    let e = CbmlError::err_number_out_of_range(
+                        self.file_path.clone(),
+                        found.span.clone(),
+                        &scope.to_string(),
+                        &range.to_string(),
+                        &found.kind.to_cbml(0),
+                    );
+                    re.push(e);
+                }
+            }
+            (CbmlType::String { constraints }, LiteralKind::String(s)) => {
+                for x in constraints.iter().filter(|x| !x.is_match(s)) {
+                    let e = CbmlError::err_string_constraint(
+                        self.file_path.clone(),
+                        found.span.clone(),
+                        &scope.to_string(),
+                        &x.describe(),
+                        &found.kind.to_cbml(0),
+                    );
+                    re.push(e);
+                }
+            }
+            (
+                CbmlType::Array {
+                    inner_type,
+                    constraints,
+                },
+                LiteralKind::Array(literals),
+            ) => {
+                for (i, x) in literals.iter().enumerate() {
+                    re.extend(self.check_literal_type(inner_type, x, &scope.index(i)));
+                }
 
-                for x in literals {
-                    if !self.is_same_type(&inner_type, x) {};
+                re.extend(self.check_array_constraints(constraints, found, literals, scope));
+            }
+            (CbmlType::Struct { fields }, LiteralKind::Struct(asignment_stmts)) => {
+                // 赋值了的字段需要有定义, 且类型相同.
+                // 没有赋值的字段在 check_unasigned_field 中检查.
+                for x in asignment_stmts {
+                    // 没有定义的字段在 check_extra_field_asign 中检查.
+                    let Some(field) = fields.iter().find(|f| f.0 == x.field_name) else {
+                        continue;
+                    };
+
+                    if let LiteralKind::Default = x.value.kind {
+                        if let Err(e) = self.check_default_literal(&x.field_name, scope, &x.value) {
+                            re.push(e);
+                        }
+                        continue;
+                    }
+
+                    let child_scope = scope.child(&x.field_name);
+                    re.extend(self.check_literal_type(&field.1, &x.value, &child_scope));
                 }
+            }
+            (CbmlType::Map { value_type }, LiteralKind::Struct(asignment_stmts)) => {
+                // map 的 key 可以是任意的名字, 只需要检查值的类型.
+                for x in asignment_stmts {
+                    // map 的值没有默认值.
+                    if let LiteralKind::Default = x.value.kind {
+                        if let Err(e) = self.check_default_literal(&x.field_name, scope, &x.value) {
+                            re.push(e);
+                        }
+                        continue;
+                    }
 
-                Ok(())
+                    let child_scope = scope.child(&x.field_name);
+                    re.extend(self.check_literal_type(value_type, &x.value, &child_scope));
+                }
             }
-            // LiteralKind::Default => Ok(()),
-            _ => {
-                let e = CbmlError::err_mismatched_types(
+            (CbmlType::Optional { inner_type }, _) => {
+                if let LiteralKind::LiteralNone = found.kind {
+                    return re;
+                }
+                re.extend(self.check_literal_type(inner_type, found, scope));
+            }
+            (CbmlType::TypeUnion { types }, _) => {
+                let (closest, errors) = self.closest_alternative(types, found, scope);
+                if errors.is_empty() {
+                    return re;
+                }
+
+                // 没有完全符合的类型, 报告最接近的那一个.
+                let mut e = CbmlError::err_mismatched_types(
                     self.file_path.clone(),
+                    found.span.clone(),
+                    &scope.to_string(),
+                    &need_type.to_cbml(0),
+                    &found.kind.to_cbml(0),
+                );
+                e.help = Some(format!("最接近的类型是 `{}`", closest.to_cbml(0)));
+                e.note = Some(
+                    errors
+                        .iter()
+                        .map(|x| x.msg.clone())
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                );
+                re.push(e);
+            }
+            (CbmlType::Named { type_name }, _) => {
+                let Some(info) = self
+                    .typedef_file
+                    .as_ref()
+                    .and_then(|x| x.get_type_by_name(type_name))
+                else {
+                    return re;
+                };
+
+                re.extend(self.check_literal_type(&info.ty, found, scope));
+            }
+            (
+                CbmlType::Enum { fields },
+                LiteralKind::EnumFieldLiteral {
+                    field_name,
+                    literal,
                     span,
-                    &CbmlType::Array {
-                        inner_type: inner_type,
+                },
+            ) => {
+                let Some(field) = fields.iter().find(|x| &x.0 == field_name) else {
+                    let candidates: Vec<&str> = fields.iter().map(|x| x.0.as_str()).collect();
+                    let e = CbmlError::err_unknow_enum_field(
+                        self.file_path.clone(),
+                        span.clone(),
+                        field_name,
+                        &candidates,
+                    );
+                    re.push(e);
+                    return re;
+                };
+
+                match (&field.1, literal) {
+                    (Some(field_type), Some(literal)) => {
+                        re.extend(self.check_literal_type(
+                            field_type,
+                            literal,
+                            &scope.variant(field_name),
+                        ));
                     }
-                    // &CbmlType {
-                    //     kind: CbmlTypeKind::Array {
-                    //         inner_type: inner_type,
-                    //     },
-                    // }
-                    .to_cbml(0),
-                    &found.to_cbml(0),
-                );
-                Err(e)
+                    (None, None) => {}
+                    _ => {
+                        // `fast(1)` 或者 `custom`, 有没有值跟定义不一样.
+                        let expected = match &field.1 {
+                            Some(field_type) => {
+                                format!("{}({})", field_name, field_type.to_cbml(0))
+                            }
+                            None => field_name.clone(),
+                        };
+                        let e = CbmlError::err_mismatched_types(
+                            self.file_path.clone(),
+                            found.span.clone(),
+                            &scope.to_string(),
+                            &expected,
+                            &found.kind.to_cbml(0),
+                        );
+                        re.push(e);
+                    }
+                }
             }
+            _ => {}
         }
+
+        return re;
     }
 
-    // fn is_same_type(&self, need_type: &CbmlType, found: &LiteralKind) -> bool {
-    fn is_same_type(&self, need_type: &CbmlType, found: &Literal) -> bool {
-        let kind = &found.kind;
-        // if let LiteralKind::Default = kind {
-        //     return true;
-        // }
-
-        match need_type.clone() {
-            CbmlType::String => match kind {
-                LiteralKind::String { .. } => true,
-                // LiteralKind::Default => true,
-                _ => false,
-            },
-            CbmlType::Number => match kind {
-                LiteralKind::Number(_) => true,
-                // LiteralKind::Default => true,
-                _ => false,
-            },
-            CbmlType::Bool => match kind {
-                LiteralKind::Boolean(_) => true,
-                // LiteralKind::Default => true,
-                _ => false,
-            },
-            CbmlType::Any => true,
-            CbmlType::Array { inner_type } => {
-                // return self.type_check_for_array(inner_type, found);
-                //
-                match kind {
-                    LiteralKind::Array(literals) => {
-                        if literals.is_empty() {
-                            return true;
-                        }
-                        return literals.iter().all(|x| self.is_same_type(&inner_type, x));
+    /// 检查数组的元素数量和元素是否重复.
+    /// found: 数组字面量, literals: 数组中的元素.
+    fn check_array_constraints(
+        &self,
+        constraints: &[ArrayConstraint],
+        found: &Literal,
+        literals: &[Literal],
+        scope: &ScopeID,
+    ) -> Vec<CbmlError> {
+        let mut re: Vec<CbmlError> = Vec::new();
+
+        for x in constraints {
+            match x {
+                ArrayConstraint::Length(range) => {
+                    if range.contains(literals.len() as f64) {
+                        continue;
                     }
-                    // LiteralKind::Default => true,
-                    _ => false,
-                }
-            }
-            CbmlType::Struct { mut fields } => {
-                //
-                {
-                    fields.sort_by(|x, y| {
-                        let x_name = &x.0;
-                        let y_name = &y.0;
-                        return x_name.cmp(&y_name);
+
+                    // 元素太多时指向第一个多出来的元素, 太少时指向整个数组.
+                    let extra = literals.iter().enumerate().find(|(i, _)| {
+                        let n = (i + 1) as f64;
+                        range.end.is_some_and(
+                            |end| {
+                                if range.inclusive { n > end } else { n >= end }
+                            },
+                        )
                     });
+                    let span = extra
+                        .map(|x| x.1.span.clone())
+                        .unwrap_or(found.span.clone());
+
+                    let e = CbmlError::err_array_length(
+                        self.file_path.clone(),
+                        span,
+                        &scope.to_string(),
+                        &range.to_string(),
+                        literals.len(),
+                    );
+                    re.push(e);
                 }
+                ArrayConstraint::Unique => {
+                    let values: Vec<CbmlValue> =
+                        literals.iter().map(|x| x.to_cbml_value()).collect();
+
+                    for (i, value) in values.iter().enumerate() {
+                        let Some(first) = values[..i].iter().position(|x| x == value) else {
+                            continue;
+                        };
+
+                        let e = CbmlError::err_array_duplicated_element(
+                            self.file_path.clone(),
+                            literals[i].span.clone(),
+                            &scope.index(i).to_string(),
+                            &scope.index(first).to_string(),
+                        );
+                        re.push(e);
+                    }
+                }
+            }
+        }
 
-                match kind {
-                    LiteralKind::Struct(asignment_stmts) => {
-                        if asignment_stmts.len() != fields.len() {
-                            // 结构体字面量数量不同,
-                            // 还有这些 field 需要填写,
-                            // 这些 field 没有定义.
-                            // TODO:
+        return re;
+    }
 
-                            return false;
-                        }
+    /// 类型是 union 时, 列出可以使用的值.
+    /// 类型是 `A | B` 并且没有形状相同的类型时, 列出可以使用的类型.
+    fn union_values_help(&self, need_type: &CbmlType) -> Option<String> {
+        match need_type {
+            CbmlType::Union { allowed_values } => {
+                let values: Vec<String> = allowed_values.iter().map(|x| x.to_cbml(0)).collect();
+                return Some(format!("可以使用的值: {}", values.join(" | ")));
+            }
+            CbmlType::TypeUnion { types } => {
+                let types: Vec<String> = types.iter().map(|x| x.to_cbml(0)).collect();
+                return Some(format!("可以使用的类型: {}", types.join(" | ")));
+            }
+            CbmlType::Optional { inner_type } => self.union_values_help(inner_type),
+            CbmlType::Named { type_name } => {
+                let info = self.typedef_file.as_ref()?.get_type_by_name(type_name)?;
+                return self.union_values_help(&info.ty);
+            }
+            _ => None,
+        }
+    }
 
-                        // let mut asignment_stmts = asignment_stmts.clone();
+    /// 找到 types 中最符合 found 的类型, 也就是错误最少的那一个.
+    /// return: (类型, 错误), 错误是空的表示完全符合.
+    fn closest_alternative<'a>(
+        &self,
+        types: &'a [CbmlType],
+        found: &Literal,
+        scope: &ScopeID,
+    ) -> (&'a CbmlType, Vec<CbmlError>) {
+        let mut closest: Option<(&CbmlType, Vec<CbmlError>)> = None;
+
+        for x in types {
+            // 形状都不一样的类型不算接近.
+            if !self.is_same_shape(x, found) {
+                continue;
+            }
 
-                        let mut key_value_pairs: Vec<(String, Literal)> = Vec::new();
-                        {
-                            for x in asignment_stmts {
-                                key_value_pairs.push((x.field_name.clone(), x.value.clone()));
-                            }
-                        }
+            let mut errors = self.check_literal_type(x, found, scope);
+            errors.extend(self.find_unknown_fields(x, found, scope));
 
-                        key_value_pairs.sort_by(|x, y| {
-                            let x_name = &x.0;
-                            let y_name = &y.0;
-                            return x_name.cmp(&y_name);
-                        });
+            let mut missing: Vec<(Span, Vec<String>)> = Vec::new();
+            self.find_missing_fields(x, found, scope, &mut missing);
+            for (span, fields) in missing {
+                let e = CbmlError::err_has_fields_unasigned(self.file_path.clone(), &fields, span);
+                errors.push(e);
+            }
 
-                        let did_it_same = fields.iter().zip(key_value_pairs).all(|(x, y)| {
-                            let x_name = &x.0;
-                            let y_name = &y.0;
-                            x_name == y_name && self.is_same_type(&x.1, &y.1)
-                        });
+            if errors.is_empty() {
+                return (x, errors);
+            }
 
-                        return did_it_same;
-                    }
-                    // LiteralKind::Todo => {
-                    //     // 不检查 todo.
+            if closest.as_ref().is_none_or(|c| errors.len() < c.1.len()) {
+                closest = Some((x, errors));
+            }
+        }
+
+        // types 不会是空的.
+        return closest.unwrap_or((&CbmlType::Any, Vec::new()));
+    }
 
-                    //     return true;
-                    // }
-                    // LiteralKind::Default => todo!("自定义 struct 类型的默认值暂时还未支持"),
-                    _ => false,
+    /// 根据类型查找没有定义的字段, 只在判断 `A | B` 符合哪一个类型时使用,
+    /// 其他时候没有定义的字段在 check_extra_field_asign 中检查.
+    fn find_unknown_fields(
+        &self,
+        need_type: &CbmlType,
+        found: &Literal,
+        scope: &ScopeID,
+    ) -> Vec<CbmlError> {
+        let mut re: Vec<CbmlError> = Vec::new();
+
+        match (need_type, &found.kind) {
+            (CbmlType::Struct { fields }, LiteralKind::Struct(asignment_stmts)) => {
+                for x in asignment_stmts {
+                    let child_scope = scope.child(&x.field_name);
+
+                    let Some(field) = fields.iter().find(|f| f.0 == x.field_name) else {
+                        let candidates: Vec<&str> = fields.iter().map(|f| f.0.as_str()).collect();
+                        let e = CbmlError::err_unknow_field(
+                            self.file_path.clone(),
+                            x.field_name_span.clone(),
+                            &child_scope.to_string(),
+                            &x.field_name,
+                            &candidates,
+                        );
+                        re.push(e);
+                        continue;
+                    };
+
+                    re.extend(self.find_unknown_fields(&field.1, &x.value, &child_scope));
                 }
             }
-            CbmlType::Optional {
-                inner_type,
-                // span: _span,
-            } => {
-                return match kind {
-                    LiteralKind::LiteralNone => true,
-                    _ => self.is_same_type(&inner_type, found),
+            (CbmlType::Array { inner_type, .. }, LiteralKind::Array(literals)) => {
+                for (i, x) in literals.iter().enumerate() {
+                    re.extend(self.find_unknown_fields(inner_type, x, &scope.index(i)));
+                }
+            }
+            (CbmlType::Map { value_type }, LiteralKind::Struct(asignment_stmts)) => {
+                for x in asignment_stmts {
+                    let child_scope = scope.child(&x.field_name);
+                    re.extend(self.find_unknown_fields(value_type, &x.value, &child_scope));
+                }
+            }
+            (CbmlType::Optional { inner_type }, _) => {
+                re.extend(self.find_unknown_fields(inner_type, found, scope));
+            }
+            (CbmlType::Named { type_name }, _) => {
+                let Some(info) = self
+                    .typedef_file
+                    .as_ref()
+                    .and_then(|x| x.get_type_by_name(type_name))
+                else {
+                    return re;
                 };
+
+                re.extend(self.find_unknown_fields(&info.ty, found, scope));
             }
-            CbmlType::Union { allowed_values } => allowed_values.contains(&found.to_cbml_value()),
-            CbmlType::Enum { fields } => match kind {
+            (
+                CbmlType::Enum { fields },
                 LiteralKind::EnumFieldLiteral {
                     field_name,
                     literal,
                     ..
-                } => {
-                    // 检查 EnumFieldLiteral 的名字是否包含在 CbmlType::Enum fields 中.
+                },
+            ) => {
+                if let (Some((_, Some(field_type))), Some(literal)) =
+                    (fields.iter().find(|x| &x.0 == field_name), literal)
+                {
+                    re.extend(self.find_unknown_fields(
+                        field_type,
+                        literal,
+                        &scope.variant(field_name),
+                    ));
+                }
+            }
+            // 嵌套的 `A | B` 在 check_literal_type 中已经检查过了.
+            _ => {}
+        }
 
-                    for x in fields {
-                        if &x.0 == field_name {
-                            return self.is_same_type(&x.1, literal);
-                        }
-                    }
+        return re;
+    }
 
-                    return false;
-                }
-                _ => false,
-            },
+    /// name = default, 字段需要有默认值.
+    fn check_default_literal(
+        &self,
+        field_name: &String,
+        scope: &ScopeID,
+        literal: &Literal,
+    ) -> Result<(), CbmlError> {
+        let has_default_value = self
+            .get_field_def(field_name, scope.clone())
+            .is_some_and(|x| x.default_value.is_some());
+
+        if has_default_value {
+            return Ok(());
         }
+
+        let e = CbmlError::err_this_field_donot_have_default_value(
+            self.file_path.clone(),
+            literal.span.clone(),
+        );
+        return Err(e);
     }
 
-    fn check_one_field_type(&self, field: &FieldAsign) -> Result<(), CbmlError> {
+    fn check_one_field_type(&self, field: &FieldAsign) -> Vec<CbmlError> {
         let Some(type_info) = self.get_field_defined_type(field) else {
-            // 这个赋值的字段并未定义过.
-            let e = CbmlError::err_unknow_field(
-                self.file_path.clone(),
-                field.span.clone(),
-                // &field.name,
-                &format!("name: {}, scope: {}", field.name, field.scope.0),
-            );
-
-            return Err(e);
+            // 这个赋值的字段并未定义过, 在 check_extra_field_asign 中检查.
+            return vec![];
         };
 
-        // todo: 一次检查一个字段, 如果这个字段的类型是 结构体, 则逐一检查每一个字段,
+        // name = default
+        if let LiteralKind::Default = field.value.kind {
+            return match self.check_default_literal(&field.name, &field.scope, &field.value) {
+                Ok(_) => vec![],
+                Err(e) => vec![e],
+            };
+        }
 
         // note: 做类型检查时, 需要先确定 top level field 的类型, 里面的字段类型才能被确定.
-
-        // 匹配 top level field 的类型.
-        if self.is_same_type(&type_info.ty, &field.value) {
-            return Ok(());
-        } else {
-            // 如果是结构体, 匹配里面每一个字段的类型, 找到出错的那个字段.
-
-            let e = CbmlError::err_mismatched_types(
-                self.file_path.clone(),
-                field.value.span.clone(),
-                &type_info.ty.to_cbml(0),
-                &field.value.kind.to_cbml(0),
-            );
-
-            return Err(e);
-        }
+        return self.check_literal_type(&type_info.ty, &field.value, &field.child_scope());
     }
 
     // 字段重复检查, 一个字段只需要赋值一次.
@@ -758,7 +1455,7 @@ impl CbmlFile {
                 CbmlError::err_field_alredy_exits(
                     self.file_path.clone(),
                     x.span.clone(),
-                    &format!("name: {}, scope: {}", x.name, x.scope.0),
+                    &x.child_scope().to_string(),
                 )
             })
             .collect();
@@ -766,48 +1463,70 @@ impl CbmlFile {
         self.errors.extend(errors);
     }
 
-    // 缺失字段检查, 检查定义了却没有赋值的 top level 字段,
+    // 缺失字段检查, 检查每一层中定义了却没有赋值的字段,
     fn check_unasigned_field(&mut self) {
-        let file_path = self.file_path.clone();
-
-        let span = self.last_line_span.clone();
-        let unasigned_field = self.get_unasigned_fields();
-        if unasigned_field.is_empty() {
-            return;
+        for (span, unasigned_fields) in self.get_missing_fields() {
+            let e = CbmlError::err_has_fields_unasigned(
+                self.file_path.clone(),
+                &unasigned_fields,
+                span,
+            );
+            self.errors.push(e);
         }
-
-        let e = CbmlError::err_has_fields_unasigned(file_path, unasigned_field, span);
-        self.errors.push(e);
     }
 
-    /// 检查那些 赋值了却并未定义的字段.
-    #[allow(dead_code)]
+    /// 检查那些 赋值了却并未定义的字段, 包括所有 scope 中的子字段.
     fn check_extra_field_asign(&mut self) {
         let Some(def_file) = &self.typedef_file else {
             return;
         };
 
+        let mut errors: Vec<CbmlError> = Vec::new();
+
         for x in &self.fields {
+            // map 的 key 可以是任意的名字.
+            if def_file.is_map_scope(&x.scope) {
+                continue;
+            }
+
             let sadf = def_file.get_field_def_by_name(x.name.clone(), x.scope.clone());
-            match sadf {
-                Some(_f) => {
-                    continue;
-                }
-                None => {
-                    // 这个赋值的字段并未定义过.
-                    let e = CbmlError::err_unknow_field(
-                        self.file_path.clone(),
-                        x.span.clone(),
-                        &x.name,
-                    );
-                    self.errors.push(e);
-                }
+            if sadf.is_some() {
+                continue;
+            }
+
+            let defined_fields = def_file.get_fields_by_scope(&x.scope);
+
+            // 父字段没有定义或者不是结构体, 父字段的错误已经报告过了.
+            if defined_fields.is_empty() && !x.scope.is_empty() {
+                continue;
             }
+
+            // 这个赋值的字段并未定义过.
+            let candidates: Vec<&str> = defined_fields.iter().map(|f| f.name.as_str()).collect();
+            let e = CbmlError::err_unknow_field(
+                self.file_path.clone(),
+                x.span.clone(),
+                &x.child_scope().to_string(),
+                &x.name,
+                &candidates,
+            );
+            errors.push(e);
         }
+
+        self.errors.extend(errors);
     }
+
     /// 缺失字段检查, 检查 struct 中定义了却没有赋值的字段.
     #[allow(dead_code)]
     fn check_struct_field(&mut self) {}
+
+    /// todo 只是警告, 不是错误.
+    fn check_todo(&mut self) {
+        for (path, span) in self.get_todo_fields() {
+            let e = CbmlError::err_field_is_todo(self.file_path.clone(), span, &path);
+            self.warnings.push(e);
+        }
+    }
 }
 
 impl ToCbml for CbmlFile {
@@ -825,9 +1544,9 @@ impl ToCbml for CbmlFile {
 
 impl ToCbmlValue for CbmlFile {
     fn to_cbml_value(&self) -> CbmlValue {
-        let mut root: HashMap<String, CbmlValue> = HashMap::new();
+        let mut root: IndexMap<String, CbmlValue> = IndexMap::new();
 
-        let root_id = ScopeID::new(String::new());
+        let root_id = ScopeID::empty();
         let top_fields: Vec<&FieldAsign> =
             self.fields.iter().filter(|x| x.scope == root_id).collect();
 
@@ -836,6 +1555,8 @@ impl ToCbmlValue for CbmlFile {
             root.insert(x.name.clone(), re);
         }
 
+        self.fill_omitted_fields(&mut root, &root_id);
+
         return CbmlValue::Struct(root);
     }
 }
@@ -846,3 +1567,1039 @@ impl CbmlFile {
         todo!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AndThenTo;
+    use crate::cbml_project::test_utils::*;
+
+    #[test]
+    fn test_default_literal() {
+        let def_code = r##"
+name: string default "hello"
+age: number
+p: {
+    city: string default "beijing"
+    zip: number
+}
+"##;
+
+        let code = r##"
+name = default
+age = 1
+p = { city = default, zip = 2 }
+"##;
+        let f = new_file(def_code, code);
+        assert!(f.errors.is_empty(), "{:?}", f.errors);
+
+        let CbmlValue::Struct(root) = f.to_cbml_value() else {
+            panic!();
+        };
+        assert_eq!(root["name"], CbmlValue::String("hello".into()));
+
+        let CbmlValue::Struct(p) = &root["p"] else {
+            panic!();
+        };
+        assert_eq!(p["city"], CbmlValue::String("beijing".into()));
+        assert_eq!(p["zip"], CbmlValue::Number(2.0));
+
+        // 没有默认值.
+        let code = r##"
+name = "a"
+age = default
+p = { city = "a", zip = default }
+"##;
+        let f = new_file(def_code, code);
+        assert_eq!(
+            error_details(code, &f.errors),
+            vec![
+                (
+                    11,
+                    "this field donot have default value".into(),
+                    "default".into()
+                ),
+                (
+                    11,
+                    "this field donot have default value".into(),
+                    "default".into()
+                ),
+            ]
+        );
+        assert_eq!(f.errors[0].span.start.line, 2);
+        assert_eq!(f.errors[1].span.start.line, 3);
+    }
+
+    #[test]
+    fn test_omitted_fields() {
+        let def_code = r##"
+name: string default "hello"
+nick: ?string
+age: number
+p: {
+    city: string default "beijing"
+    zip: ?number
+    street: string
+}
+list: [{
+    a: number default 1
+    b: number
+}]
+e: enum {
+    x({
+        a: number default 2
+        b: ?bool
+    })
+}
+"##;
+
+        let code = r##"
+age = 1
+p = { street = "s" }
+list = [{ b = 2 }]
+e = x({})
+"##;
+        let f = new_file(def_code, code);
+        assert!(f.errors.is_empty(), "{:?}", f.errors);
+
+        let struct_value = |fields: Vec<(&str, CbmlValue)>| {
+            CbmlValue::Struct(fields.into_iter().map(|x| (x.0.to_string(), x.1)).collect())
+        };
+
+        let expected = struct_value(vec![
+            ("name", CbmlValue::String("hello".into())),
+            ("nick", CbmlValue::None),
+            ("age", CbmlValue::Number(1.0)),
+            (
+                "p",
+                struct_value(vec![
+                    ("city", CbmlValue::String("beijing".into())),
+                    ("zip", CbmlValue::None),
+                    ("street", CbmlValue::String("s".into())),
+                ]),
+            ),
+            (
+                "list",
+                CbmlValue::Array(vec![struct_value(vec![
+                    ("a", CbmlValue::Number(1.0)),
+                    ("b", CbmlValue::Number(2.0)),
+                ])]),
+            ),
+            (
+                "e",
+                CbmlValue::EnumField(
+                    "x".into(),
+                    Box::new(struct_value(vec![
+                        ("a", CbmlValue::Number(2.0)),
+                        ("b", CbmlValue::None),
+                    ])),
+                ),
+            ),
+        ]);
+        assert_eq!(f.to_cbml_value(), expected);
+
+        // 没有默认值的字段还是需要赋值.
+        let code = r##"
+p = { street = "s" }
+list = []
+e = x({})
+"##;
+        let f = new_file(def_code, code);
+        // 顶级字段缺失时指向文件的最后一行.
+        assert_eq!(
+            error_details(code, &f.errors),
+            vec![(15, "还有 1 个字段未赋值: age".into(), "".into())]
+        );
+    }
+
+    #[test]
+    fn test_default_not_allowed() {
+        let def_code = r##"
+arr: [number]
+e: enum {
+    a(number)
+}
+"##;
+        let code = r##"
+arr = [1, default]
+e = a(default)
+"##;
+        let f = new_file(def_code, code);
+        let msg = "default 关键字不能在这里使用.";
+        assert_eq!(
+            error_details(code, &f.errors),
+            vec![
+                (13, msg.into(), "default".into()),
+                (13, msg.into(), "default".into()),
+            ]
+        );
+        assert_eq!(f.errors[0].span.start.line, 1);
+        assert_eq!(f.errors[1].span.start.line, 2);
+
+        let def_code = "name: string default default";
+        let def_file = new_def_file(def_code);
+        assert_eq!(
+            error_details(def_code, &def_file.errors),
+            vec![(13, msg.into(), "default".into())]
+        );
+    }
+
+    #[test]
+    fn test_todo() {
+        let def_code = r##"
+name: string
+p: {
+    city: string
+    zip: number
+}
+list: [number]
+e: enum {
+    a(string)
+}
+"##;
+        let code = r##"
+name = todo
+p = { city = "beijing", zip = todo }
+list = [1, todo]
+e = a(todo)
+"##;
+        let f = new_file(def_code, code);
+        assert!(f.errors.is_empty(), "{:?}", f.errors);
+
+        let codes: Vec<u32> = f.warnings.iter().map(|x| x.error_code).collect();
+        assert_eq!(codes, vec![24, 24, 24, 24]);
+
+        let paths: Vec<String> = f.get_todo_fields().into_iter().map(|x| x.0).collect();
+        assert_eq!(paths, vec!["name", "p.zip", "list[1]", "e.a"]);
+
+        assert_eq!(
+            f.to_cbml_value().key_path(["name"]).cloned(),
+            Some(CbmlValue::Todo)
+        );
+
+        let e = f.try_to_cbml_value().unwrap_err();
+        assert_eq!(e.len(), 1);
+        assert_eq!(e[0].error_code, 24);
+        assert_eq!(e[0].msg, "field `name` is still todo");
+
+        // 先返回类型检查的错误.
+        let f = new_file(def_code, "name = 1\np = todo\nlist = []\ne = a(\"x\")");
+        let e = f.try_to_cbml_value().unwrap_err();
+        let codes: Vec<u32> = e.iter().map(|x| x.error_code).collect();
+        assert_eq!(codes, vec![4]);
+
+        let f = new_file(
+            def_code,
+            r##"
+name = "n"
+p = { city = "beijing", zip = 1 }
+list = []
+e = a("x")
+"##,
+        );
+        assert!(f.warnings.is_empty());
+        assert!(f.try_to_cbml_value().is_ok());
+    }
+
+    #[test]
+    fn test_nested_missing_fields() {
+        let def_code = r##"
+name: string
+p: {
+    city: string
+    zip: ?number
+    street: string default "s"
+    inner: {
+        a: number
+        b: number
+    }
+}
+list: [{
+    a: number
+    b: ?number
+}]
+e: enum {
+    x({
+        a: number
+        b: number
+    })
+}
+"##;
+        let code = r##"
+p = { inner = { a = 1 } }
+list = [{ a = 1 }, { b = 2 }]
+e = x({})
+"##;
+        let f = new_file(def_code, code);
+        assert_eq!(
+            error_details(code, &f.errors),
+            vec![
+                (15, "还有 1 个字段未赋值: name".into(), "".into()),
+                (
+                    15,
+                    "还有 1 个字段未赋值: p.city".into(),
+                    "{ inner = { a = 1 } }".into()
+                ),
+                (
+                    15,
+                    "还有 1 个字段未赋值: p.inner.b".into(),
+                    "{ a = 1 }".into()
+                ),
+                (
+                    15,
+                    "还有 1 个字段未赋值: list[1].a".into(),
+                    "{ b = 2 }".into()
+                ),
+                (15, "还有 2 个字段未赋值: e.x.a, e.x.b".into(), "{}".into()),
+            ]
+        );
+
+        let mut missing: Vec<Vec<String>> =
+            f.get_missing_fields().into_iter().map(|x| x.1).collect();
+        missing.sort();
+        assert_eq!(
+            missing,
+            vec![
+                vec!["e.x.a".to_string(), "e.x.b".to_string()],
+                vec!["list[1].a".to_string()],
+                vec!["name".to_string()],
+                vec!["p.city".to_string()],
+                vec!["p.inner.b".to_string()],
+            ]
+        );
+
+        let code = r##"
+name = "n"
+p = { city = "c", inner = { a = 1, b = 2 } }
+list = [{ a = 1 }]
+e = x({ a = 1, b = 2 })
+"##;
+        let f = new_file(def_code, code);
+        assert!(f.errors.is_empty(), "{:?}", f.errors);
+    }
+
+    #[test]
+    fn test_mismatched_types_per_node() {
+        let def_code = r##"
+tree: {
+    age: number
+    obj: {
+        name: string
+        obj: {
+            age: number
+        }
+    }
+}
+list: [number]
+e: enum {
+    a(?bool)
+}
+"##;
+        let code = r##"
+tree = { age = 1, obj = { name = 2, obj = { age = "x" } } }
+list = [1, "2", 3, true]
+e = a(1)
+"##;
+        let f = new_file(def_code, code);
+        // 错误的位置是值自己的位置, 而不是整个 top level 字面量.
+        assert_eq!(
+            error_details(code, &f.errors),
+            vec![
+                (
+                    4,
+                    "mismatched types at `tree.obj.name`, expected `string` found `2`".into(),
+                    "2".into()
+                ),
+                (
+                    4,
+                    "mismatched types at `tree.obj.obj.age`, expected `number` found `\"x\"`"
+                        .into(),
+                    "\"x\"".into()
+                ),
+                (
+                    4,
+                    "mismatched types at `list[1]`, expected `number` found `\"2\"`".into(),
+                    "\"2\"".into()
+                ),
+                (
+                    4,
+                    "mismatched types at `list[3]`, expected `number` found `true`".into(),
+                    "true".into()
+                ),
+                (
+                    4,
+                    "mismatched types at `e.a`, expected `?bool` found `1`".into(),
+                    "1".into()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        let def_code = r##"
+name: string
+p: {
+    city: string
+    street: string
+}
+list: [{
+    count: number
+}]
+e: enum {
+    github({ url: string })
+    local(string)
+}
+"##;
+        let code = r##"
+nmae = "a"
+p = { city = "c", stret = "s", zzz = 1 }
+list = [{ count = 1, cuont = 2 }]
+e = githup({ url = "u" })
+other = { a = 1 }
+"##;
+        let f = new_file(def_code, code);
+
+        let mut re: Vec<(u32, String, Option<String>)> = f
+            .errors
+            .iter()
+            .filter(|x| x.error_code != 15)
+            .map(|x| (x.error_code, x.msg.clone(), x.help.clone()))
+            .collect();
+        re.sort();
+
+        let did_you_mean = |x: &str| Some(format!("did you mean `{}`?", x));
+        assert_eq!(
+            re,
+            vec![
+                (
+                    3,
+                    "unknow field `list[0].cuont` ".into(),
+                    did_you_mean("count")
+                ),
+                (3, "unknow field `nmae` ".into(), did_you_mean("name")),
+                (3, "unknow field `other` ".into(), None),
+                (3, "unknow field `p.stret` ".into(), did_you_mean("street")),
+                (3, "unknow field `p.zzz` ".into(), None),
+                (
+                    25,
+                    "unknow enum field `githup`".into(),
+                    did_you_mean("github")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_array_element_scope() {
+        let def_code = r##"
+list: [{
+    name: string
+}]
+"##;
+        let f = new_file(def_code, r##"list = [{ name = "a" }, { name = "b" }]"##);
+        assert!(f.errors.is_empty(), "{:?}", f.errors);
+
+        let scopes: Vec<ScopeID> = f
+            .fields
+            .iter()
+            .filter(|x| x.name == "name")
+            .map(|x| x.scope.clone())
+            .collect();
+        let list = ScopeID::empty().child("list");
+        assert_eq!(scopes, vec![list.index(0), list.index(1)]);
+        assert_eq!(scopes[1].child("name").to_string(), "list[1].name");
+
+        let code = r##"list = [{ name = "a" }, { name = "b", name = "c" }]"##;
+        let f = new_file(def_code, code);
+        assert_eq!(
+            error_details(code, &f.errors),
+            vec![(8, "field `list[1].name` alredy exit".into(), "name".into())]
+        );
+        assert_eq!(f.errors[0].span.start.character_index, 38);
+    }
+
+    #[test]
+    fn test_type_union() {
+        let def_code = r##"
+port: number | string
+source: Git | Dir
+items: [number | {
+    name: string
+}]
+
+struct Git {
+    url: string
+    branch: string default "main"
+}
+
+struct Dir {
+    path: string
+    recursive: ?bool
+}
+"##;
+        let code = r##"
+port = "8080"
+source = { url = "u" }
+items = [1, { name = "a" }]
+"##;
+        let f = new_file(def_code, code);
+        assert!(f.errors.is_empty(), "{:?}", f.errors);
+
+        // 只使用 Git 的默认值, 不会填充 Dir 的可选字段.
+        let Some(CbmlValue::Struct(source)) = f.to_cbml_value().key_path(["source"]).cloned()
+        else {
+            panic!();
+        };
+        assert_eq!(source.len(), 2);
+        assert_eq!(source["branch"], CbmlValue::String("main".into()));
+
+        let code = r##"
+port = true
+source = { path = 1 }
+items = []
+"##;
+        let f = new_file(def_code, code);
+        assert_eq!(
+            error_details(code, &f.errors),
+            vec![
+                (
+                    4,
+                    "mismatched types at `port`, expected `number | string` found `true`".into(),
+                    "true".into()
+                ),
+                (
+                    4,
+                    "mismatched types at `source`, expected `Git | Dir` found `{\n    path = 1\n}`"
+                        .into(),
+                    "{ path = 1 }".into()
+                ),
+            ]
+        );
+        assert_eq!(
+            f.errors[0].help.as_deref(),
+            Some("可以使用的类型: number | string")
+        );
+        assert_eq!(f.errors[1].help.as_deref(), Some("最接近的类型是 `Dir`"));
+        assert_eq!(
+            f.errors[1].note.as_deref(),
+            Some("mismatched types at `source.path`, expected `string` found `1`")
+        );
+    }
+
+    #[test]
+    fn test_map_type() {
+        let def_code = r##"
+env: { [string]: string }
+deps: {
+    [string]: {
+        version: string
+        optional: ?bool
+    }
+}
+"##;
+        let code = r##"
+env = { HOME = "/root", LANG = 1 }
+deps = {
+    serde = { version = "1.0" }
+    regex = { versoin = "1" }
+}
+"##;
+        let f = new_file(def_code, code);
+        assert_eq!(
+            error_details(code, &f.errors),
+            vec![
+                (
+                    15,
+                    "还有 1 个字段未赋值: deps.regex.version".into(),
+                    "{ versoin = \"1\" }".into()
+                ),
+                (
+                    4,
+                    "mismatched types at `env.LANG`, expected `string` found `1`".into(),
+                    "1".into()
+                ),
+                (
+                    3,
+                    "unknow field `deps.regex.versoin` ".into(),
+                    "versoin".into()
+                ),
+            ]
+        );
+        assert_eq!(f.errors[2].help.as_deref(), Some("did you mean `version`?"));
+
+        let code = r##"
+env = { PATH = "/bin", HOME = "/root", LANG = "C" }
+deps = { serde = { version = "1.0" } }
+"##;
+        let f = new_file(def_code, code);
+        assert!(f.errors.is_empty(), "{:?}", f.errors);
+
+        // map 保留书写的顺序.
+        let value = f.to_cbml_value();
+        let keys: Vec<&str> = value
+            .key_path(["env"])
+            .cbml_map()
+            .unwrap()
+            .iter()
+            .map(|x| x.0.as_str())
+            .collect();
+        assert_eq!(keys, vec!["PATH", "HOME", "LANG"]);
+
+        // map 中的结构体会填充可选字段.
+        let deps = value.key_path(["deps"]).cbml_map().unwrap();
+        assert_eq!(deps[0].0, "serde");
+        assert_eq!(
+            deps[0].1.cbml_struct().unwrap()["optional"],
+            CbmlValue::None
+        );
+
+        // 不是标识符的 key 使用字符串.
+        let code = r##"
+env = { "_JAVA_OPTIONS" = "-Xmx1g", "type" = "x", 名字 = "a" }
+deps = { "serde-json" = { version = "1.0" } }
+"##;
+        let f = new_file(def_code, code);
+        assert!(f.errors.is_empty(), "{:?}", f.errors);
+        let value = f.to_cbml_value();
+        let env = value.key_path(["env"]).cbml_map().unwrap();
+        let keys: Vec<&str> = env.iter().map(|x| x.0.as_str()).collect();
+        assert_eq!(keys, vec!["_JAVA_OPTIONS", "type", "名字"]);
+        assert_eq!(
+            value.key_path(["env"]).unwrap().to_cbml(0),
+            "{\n    \"_JAVA_OPTIONS\" = \"-Xmx1g\"\n    type = \"x\"\n    名字 = \"a\"\n}"
+        );
+        assert_eq!(
+            value.key_path(["deps"]).cbml_map().unwrap()[0].0,
+            "serde-json"
+        );
+
+        let f = new_file(
+            def_code,
+            "env = {}\ndeps = { \"serde-json\" = { versoin = \"1\" } }",
+        );
+        assert!(
+            f.errors
+                .iter()
+                .any(|x| x.msg.contains("deps.serde-json.version")),
+            "{:?}",
+            f.errors
+        );
+
+        let def_file = new_def_file("m: { [number]: string }");
+        assert_eq!(def_file.errors.len(), 1);
+        assert_eq!(def_file.errors[0].error_code, 26);
+    }
+
+    #[test]
+    fn test_number_range() {
+        let def_code = r##"
+port: int(1..=65535)
+ratio: number(0..1)
+retries: int
+ids: [int(0..)]
+"##;
+        let code = r##"
+port = -3
+ratio = 1
+retries = 2.5
+ids = [1, -1]
+"##;
+        let f = new_file(def_code, code);
+        assert_eq!(
+            error_details(code, &f.errors),
+            vec![
+                (
+                    27,
+                    "value of `port` is out of range, expected `1..=65535` found `-3`".into(),
+                    "-3".into()
+                ),
+                (
+                    27,
+                    "value of `ratio` is out of range, expected `0..1` found `1`".into(),
+                    "1".into()
+                ),
+                (
+                    4,
+                    "mismatched types at `retries`, expected `int` found `2.5`".into(),
+                    "2.5".into()
+                ),
+                (
+                    27,
+                    "value of `ids[1]` is out of range, expected `0..` found `-1`".into(),
+                    "-1".into()
+                ),
+            ]
+        );
+        assert_eq!(
+            f.errors[0].help.as_deref(),
+            Some("允许的范围是 `1..=65535`")
+        );
+
+        let code = r##"
+port = 8080
+ratio = 0.5
+retries = 3
+ids = [0, 7]
+"##;
+        let f = new_file(def_code, code);
+        assert!(f.errors.is_empty(), "{:?}", f.errors);
+
+        let def_file = new_def_file("a: int(10..1)");
+        assert_eq!(def_file.errors.len(), 1);
+        assert_eq!(def_file.errors[0].error_code, 28);
+    }
+
+    #[test]
+    fn test_string_constraints() {
+        let def_code = r##"
+name: string(1..)
+version: string(semver)
+email: ?string(email)
+code: string("^[A-Z]+$", 3..=3)
+host: string(1..=253, hostname)
+"##;
+        let code = r##"
+name = ""
+version = "1.0"
+email = "a b"
+code = "abcd"
+host = "-a"
+"##;
+        let f = new_file(def_code, code);
+        let details = error_details(code, &f.errors);
+        assert_eq!(
+            details,
+            vec![
+                (
+                    31,
+                    r#"value of `name` does not satisfy length `1..`, found `""`"#.into(),
+                    "\"\"".into()
+                ),
+                (
+                    31,
+                    r#"value of `version` does not satisfy format `semver`, found `"1.0"`"#.into(),
+                    r#""1.0""#.into()
+                ),
+                (
+                    31,
+                    r#"value of `email` does not satisfy format `email`, found `"a b"`"#.into(),
+                    r#""a b""#.into()
+                ),
+                (
+                    31,
+                    r#"value of `code` does not satisfy pattern `^[A-Z]+$`, found `"abcd"`"#.into(),
+                    r#""abcd""#.into()
+                ),
+                (
+                    31,
+                    r#"value of `code` does not satisfy length `3..=3`, found `"abcd"`"#.into(),
+                    r#""abcd""#.into()
+                ),
+                (
+                    31,
+                    r#"value of `host` does not satisfy format `hostname`, found `"-a"`"#.into(),
+                    r#""-a""#.into()
+                ),
+            ]
+        );
+
+        let code = r##"
+name = "a"
+version = "1.0.0-rc.1"
+email = none
+code = "ABC"
+host = "example.com"
+"##;
+        let f = new_file(def_code, code);
+        assert!(f.errors.is_empty(), "{:?}", f.errors);
+
+        let def_file = new_def_file("a: string(emial)");
+        let e = def_file.errors.iter().find(|x| x.error_code == 29).unwrap();
+        assert_eq!(e.help.as_deref(), Some("did you mean `email`?"));
+
+        let def_file = new_def_file(r#"a: string("(")"#);
+        assert_eq!(def_file.errors.len(), 1);
+        assert_eq!(def_file.errors[0].error_code, 30);
+        assert!(def_file.errors[0].msg.starts_with("invalid regex: "));
+        // 错误的位置是正则表达式, 不是整个类型.
+        assert_eq!(def_file.errors[0].span.start.character_index, 10);
+        assert_eq!(def_file.errors[0].span.end.character_index, 13);
+    }
+
+    #[test]
+    fn test_array_constraints() {
+        let def_code = r##"
+tags: [string](1..=3, unique)
+ports: [int](..2)
+"##;
+        let code = r##"
+tags = ["a", "b", "a", "c", "d"]
+ports = [1, 2]
+"##;
+        let f = new_file(def_code, code);
+        assert_eq!(
+            error_details(code, &f.errors),
+            vec![
+                (
+                    32,
+                    "length of `tags` is out of range, expected `1..=3` found `5`".into(),
+                    r#""c""#.into()
+                ),
+                (
+                    33,
+                    "duplicated element `tags[2]`, same as `tags[0]`".into(),
+                    r#""a""#.into()
+                ),
+                (
+                    32,
+                    "length of `ports` is out of range, expected `..2` found `2`".into(),
+                    "2".into()
+                ),
+            ]
+        );
+        // 元素太多时指向第一个多余的元素, 重复的元素指向后面的那一个.
+        assert!(code[f.errors[0].span.start.character_index..].starts_with(r#""c", "d""#));
+        assert!(code[f.errors[1].span.start.character_index..].starts_with(r#""a", "c""#));
+
+        // 元素太少时指向整个数组.
+        let code = r##"
+tags = []
+ports = [1]
+"##;
+        let f = new_file(def_code, code);
+        assert_eq!(
+            error_details(code, &f.errors),
+            vec![(
+                32,
+                "length of `tags` is out of range, expected `1..=3` found `0`".into(),
+                "[]".into()
+            )]
+        );
+        assert_eq!(
+            f.errors[0].help.as_deref(),
+            Some("允许的元素数量是 `1..=3`")
+        );
+
+        let def_file = new_def_file("a: [string](uniqe)");
+        let e = def_file.errors.iter().find(|x| x.error_code == 34).unwrap();
+        assert_eq!(e.help.as_deref(), Some("did you mean `unique`?"));
+
+        // 不能是空数组的 `[T](1..)` 不能用来包裹递归类型.
+        let def_code = r##"
+struct Node {
+    children: [Node](1..)
+}
+"##;
+        let def_file = new_def_file(def_code);
+        assert_eq!(def_file.errors.len(), 1);
+        assert_eq!(def_file.errors[0].error_code, 22);
+    }
+
+    #[test]
+    fn test_unit_enum_variants() {
+        let def_code = r##"
+mode: enum {
+    fast
+    slow
+    custom(number)
+}
+level: Level
+enum Level { debug
+    info
+    warn }
+"##;
+        let code = r##"
+mode = fast
+level = info
+"##;
+        let f = new_file(def_code, code);
+        assert!(f.errors.is_empty(), "{:?}", f.errors);
+
+        let value = f.to_cbml_value();
+        assert_eq!(value.key_path(["mode"]).cbml_enum_unit(), Some("fast"));
+        assert_eq!(
+            value.key_path(["level"]),
+            Some(&CbmlValue::EnumUnit("info".into()))
+        );
+
+        let code = r##"
+mode = custom(2)
+level = warn
+"##;
+        let f = new_file(def_code, code);
+        assert!(f.errors.is_empty(), "{:?}", f.errors);
+
+        // 有没有值跟定义不一样.
+        let code = r##"
+mode = fast(1)
+level = info
+"##;
+        let f = new_file(def_code, code);
+        assert_eq!(
+            error_details(code, &f.errors),
+            vec![(
+                4,
+                "mismatched types at `mode`, expected `fast` found `fast(1)`".into(),
+                "fast(1)".into()
+            )]
+        );
+
+        let code = r##"
+mode = custom
+level = error
+"##;
+        let f = new_file(def_code, code);
+        assert_eq!(
+            error_details(code, &f.errors),
+            vec![
+                (
+                    4,
+                    "mismatched types at `mode`, expected `custom(number)` found `custom`".into(),
+                    "custom".into()
+                ),
+                (25, "unknow enum field `error`".into(), "error".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_field_order() {
+        let def_code = r##"
+name: string
+port: number default 80
+owner: ?string
+p: { zip: number, city: string }
+"##;
+        let code = r##"
+p = { city = "beijing", zip = 1 }
+name = "cbml"
+"##;
+        let f = new_file(def_code, code);
+        assert!(f.errors.is_empty(), "{:?}", f.errors);
+
+        // 书写的字段在前, 省略的字段按照定义的顺序在后.
+        let val = f.to_cbml_value();
+        let keys: Vec<&String> = val.cbml_struct().unwrap().keys().collect();
+        assert_eq!(keys, ["p", "name", "port", "owner"]);
+
+        let p = val.key_path(["p"]).unwrap().to_cbml(0);
+        assert_eq!(p, "{\n    city = \"beijing\"\n    zip = 1\n}");
+
+        let def_file = new_def_file(def_code);
+        let names: Vec<&str> = def_file
+            .get_all_top_fields()
+            .iter()
+            .map(|x| x.name.as_str())
+            .collect();
+        assert_eq!(names, ["name", "port", "owner", "p"]);
+
+        let def_code = r##"
+/// 名字
+name: string
+who: Person
+p: { zip: number, city: string }
+tree: Node
+
+/// 人
+struct Person {
+    name: string
+    age: number default 1
+}
+
+type id = string
+
+struct Node {
+    id: id
+    children: [Node]
+}
+
+enum Kind {
+    a(string)
+    b
+}
+
+union(string) names = "a" | "b"
+kind: Kind
+names: ?names
+"##;
+        let def_file = new_def_file(def_code);
+        assert!(def_file.errors.is_empty(), "{:?}", def_file.errors);
+
+        // 具名类型保持原来的写法, 类型定义和字段定义按照在代码中的顺序.
+        let out = def_file.to_cbml(0);
+        assert!(
+            out.starts_with("/// 名字\nname: string\nwho: Person\np: {\n"),
+            "{}",
+            out
+        );
+        assert!(
+            out.contains("\ntree: Node\n/// 人\nstruct Person {\n"),
+            "{}",
+            out
+        );
+        assert!(out.ends_with("\nkind: Kind\nnames: ?names\n"), "{}", out);
+
+        // 输出的内容可以解析回来, 得到相同的定义.
+        let schema = |f: &DefCbmlFile| -> Vec<(ScopeID, CbmlType, Option<String>)> {
+            f.fields_map
+                .values()
+                .map(|x| (x.child_scope(), x.type_.ty.clone(), x.doc.clone()))
+                .collect()
+        };
+        let parsed = new_def_file(&out);
+        assert!(parsed.errors.is_empty(), "{:?}\n{}", parsed.errors, out);
+        assert_eq!(schema(&parsed), schema(&def_file));
+        let mut types: Vec<&String> = parsed.types_map.keys().collect();
+        types.sort();
+        assert_eq!(types, ["Kind", "Node", "Person", "id", "names"]);
+        assert_eq!(parsed.to_cbml(0), out);
+    }
+
+    #[test]
+    fn test_keyword_field_names() {
+        let def_code = r##"
+type: string
+int: int(1..)
+source: { type: string, default: bool }
+
+/// 类型别名
+type id = string
+struct Person {
+    type: id
+}
+who: Person
+"##;
+        let code = r##"
+type = "git"
+int = 2
+source = { type = "path", default = true }
+who = { type = "admin" }
+"##;
+        let f = new_file(def_code, code);
+        assert!(f.errors.is_empty(), "{:?}", f.errors);
+
+        let val = f.to_cbml_value();
+        assert_eq!(val.key_path(["type"]).cbml_str(), Some("git"));
+        assert_eq!(val.key_path(["int"]).cbml_number(), Some(2.0));
+        assert_eq!(val.key_path(["source", "type"]).cbml_str(), Some("path"));
+        assert_eq!(val.key_path(["who", "type"]).cbml_str(), Some("admin"));
+
+        // 在类型的位置上 int 还是类型.
+        let code = r##"
+type = "git"
+int = 0
+source = { type = "a", default = false }
+who = { type = "b" }
+"##;
+        let f = new_file(def_code, code);
+        assert_eq!(
+            error_details(code, &f.errors),
+            vec![(
+                27,
+                "value of `int` is out of range, expected `1..` found `0`".into(),
+                "0".into()
+            )]
+        );
+    }
+}
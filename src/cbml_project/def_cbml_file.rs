@@ -2,7 +2,7 @@
 use std::path::PathBuf;
 
 use super::types::*;
-use crate::cbml_data::cbml_type::CbmlType;
+use crate::cbml_data::cbml_type::{ArrayConstraint, CbmlType, NumberRange, StringConstraint};
 use crate::cbml_data::cbml_value::*;
 use crate::lexer::token::Span;
 use crate::lexer::tokenize;
@@ -10,6 +10,7 @@ use crate::parser::CbmlParser;
 use crate::parser::ast::stmt::*;
 use crate::parser::parser_error::CbmlError;
 use crate::{ToCbml, ToCbmlValue};
+use indexmap::IndexMap;
 use std::collections::HashMap;
 
 /// .def.cbml
@@ -17,16 +18,44 @@ use std::collections::HashMap;
 pub struct DefCbmlFile {
     pub file_path: String,
 
-    /// 所有字段, 包裹子字段.
+    /// 所有字段, 包裹子字段, 按照在代码中的顺序.
     /// 顶级字段的 scope_id == ScopeID::empty()
-    pub fields_map: HashMap<(String, ScopeID), FieldDef>,
+    pub fields_map: IndexMap<(String, ScopeID), FieldDef>,
+
+    /// 所有具名类型, struct enum union.
+    /// 具名类型的字段定义保存在 fields_map 中, scope_id 为 `[Type(类型名)]`.
+    pub types_map: HashMap<String, TypeInfo>,
+
+    /// 字段的子 scope 指向具名类型的 scope.
+    /// `who: Person` 会记录 `[Field(who)]` -> `[Type(Person)]`.
+    /// `source: Git | Dir` 会记录 `[Field(source)]` -> `[Type(Git)]`, `[Type(Dir)]`.
+    pub scope_alias: HashMap<ScopeID, Vec<ScopeID>>,
+
+    /// 类型是 map 的字段的子 scope, 以及这个 scope 下面有几层 map 的 key.
+    /// `env: { [string]: { [string]: number } }` 会记录 `[Field(env)]` -> 2.
+    /// map 的值的字段定义跟数组一样保存在这个 scope 中, 查找定义时跳过 key.
+    pub map_scopes: HashMap<ScopeID, usize>,
 
     pub errors: Vec<CbmlError>,
 
+    /// 文件中的语句, 按照在代码中的顺序, 输出 .def.cbml 时使用.
+    pub stmts: Vec<Stmt>,
+
     /// 解析 ast 时记录正在解析的语句所在的 scope.
-    _current_scope: Vec<ScopeID>,
+    _current_scope: ScopeID,
 
     _type_id: usize,
+
+    /// 还未解析的具名类型定义, 具名类型可以在定义之前使用.
+    _type_defs: HashMap<String, TypeDefStmt>,
+
+    /// 正在解析的具名类型, 用来检测循环引用.
+    /// (类型名, 是否是 type alias, 开始解析时的 _indirection_depth)
+    _resolving_types: Vec<(String, bool, usize)>,
+
+    /// 正在解析的类型被 `?T` `[T]` `enum` 包裹的层数.
+    /// 递归类型需要被包裹, 否则这个类型的值是无限大的.
+    _indirection_depth: usize,
 }
 
 impl DefCbmlFile {
@@ -35,10 +64,17 @@ impl DefCbmlFile {
             file_path: file_path.clone(),
 
             errors: Vec::new(),
-            _current_scope: Vec::new(),
+            stmts: Vec::new(),
+            _current_scope: ScopeID::empty(),
             // count: 0,
-            fields_map: HashMap::new(),
+            fields_map: IndexMap::new(),
+            types_map: HashMap::new(),
+            scope_alias: HashMap::new(),
+            map_scopes: HashMap::new(),
             _type_id: 0,
+            _type_defs: HashMap::new(),
+            _resolving_types: Vec::new(),
+            _indirection_depth: 0,
         };
 
         if file_path.ends_with(".def.cbml") {
@@ -65,10 +101,17 @@ impl DefCbmlFile {
             // types: HashMap::new(),
             // fields: Vec::new(),
             errors: Vec::new(),
-            _current_scope: Vec::new(),
+            stmts: Vec::new(),
+            _current_scope: ScopeID::empty(),
             // count: 0,
-            fields_map: HashMap::new(),
+            fields_map: IndexMap::new(),
+            types_map: HashMap::new(),
+            scope_alias: HashMap::new(),
+            map_scopes: HashMap::new(),
             _type_id: 0,
+            _type_defs: HashMap::new(),
+            _resolving_types: Vec::new(),
+            _indirection_depth: 0,
         };
 
         if file_path.ends_with(".def.cbml") {
@@ -90,8 +133,88 @@ impl DefCbmlFile {
     }
 
     pub fn get_field_def_by_name(&self, name: String, scope: ScopeID) -> Option<&FieldDef> {
-        let key = (name, scope);
-        self.fields_map.get(&key)
+        for x in self.resolve_scopes(&scope) {
+            if let Some(def) = self.fields_map.get(&(name.clone(), x)) {
+                return Some(def);
+            }
+        }
+
+        return None;
+    }
+
+    /// 获取具名类型.
+    pub fn get_type_by_name(&self, type_name: &str) -> Option<&TypeInfo> {
+        self.types_map.get(type_name)
+    }
+
+    /// 将 `who.address` 这样的 scope 转换为字段定义所在的 scope.
+    /// 如果 `who: Person`, 则返回 `[Type(Person), Field(address)]`.
+    /// 字段定义中没有数组下标, 所以 `list[0].a` 跟 `list[1].a` 的定义是同一个.
+    /// map 的 key 也跟数组下标一样被跳过, 所以 `env.HOME.a` 的定义是 `[Field(env), Field(a)]`.
+    /// `source: Git | Dir` 时 `source` 对应两个类型, 所以会返回多个 scope.
+    pub fn resolve_scopes(&self, scope: &ScopeID) -> Vec<ScopeID> {
+        self.resolve_scopes_with_keys(scope)
+            .into_iter()
+            .map(|x| x.0)
+            .collect()
+    }
+
+    /// scope 中的下一段是不是 map 的 key, 是的话这一层的字段名可以是任意的名字.
+    pub fn is_map_scope(&self, scope: &ScopeID) -> bool {
+        self.resolve_scopes_with_keys(scope).iter().any(|x| x.1 > 0)
+    }
+
+    /// return: (scope, 接下来还需要跳过几层 map 的 key).
+    fn resolve_scopes_with_keys(&self, scope: &ScopeID) -> Vec<(ScopeID, usize)> {
+        let mut re: Vec<(ScopeID, usize)> = vec![(ScopeID::empty(), 0)];
+
+        for x in &scope.0 {
+            if let ScopeSegment::Index(_) = x {
+                continue;
+            }
+
+            let mut next: Vec<(ScopeID, usize)> = Vec::new();
+            for (s, keys) in &re {
+                if *keys > 0 {
+                    // 这一段是 map 的 key.
+                    next.push((s.clone(), keys - 1));
+                    continue;
+                }
+
+                self.follow_scope_alias(s.join(x.clone()), 0, 0, &mut next);
+            }
+            re = next;
+        }
+
+        return re;
+    }
+
+    /// `type A = B` 时 `[Type(A)]` -> `[Type(B)]`, 需要一直跳转到最终的类型.
+    /// keys: 跳转过程中遇到的 map 的层数.
+    fn follow_scope_alias(
+        &self,
+        scope: ScopeID,
+        count: usize,
+        keys: usize,
+        re: &mut Vec<(ScopeID, usize)>,
+    ) {
+        let keys = keys.max(self.map_scopes.get(&scope).copied().unwrap_or(0));
+
+        // 别名有循环引用时 scope_alias 也是循环的, 所以需要限制跳转次数.
+        let Some(aliases) = self
+            .scope_alias
+            .get(&scope)
+            .filter(|_| count <= self.scope_alias.len())
+        else {
+            if !re.iter().any(|x| x.0 == scope) {
+                re.push((scope, keys));
+            }
+            return;
+        };
+
+        for x in aliases {
+            self.follow_scope_alias(x.clone(), count + 1, keys, re);
+        }
     }
 
     /// goto_difinition 的时候会用到.
@@ -115,10 +238,16 @@ impl DefCbmlFile {
     }
 
     pub fn get_all_top_fields(&self) -> Vec<&FieldDef> {
-        let top_scope = ScopeID::new(String::new());
+        self.get_fields_by_scope(&ScopeID::empty())
+    }
+
+    /// 获取某个 scope 中定义的所有字段.
+    pub fn get_fields_by_scope(&self, scope: &ScopeID) -> Vec<&FieldDef> {
+        let scopes = self.resolve_scopes(scope);
+
         self.fields_map
             .iter()
-            .filter(|x| x.1.scope_id == top_scope)
+            .filter(|x| scopes.contains(&x.1.scope_id))
             .map(|x| x.1)
             .collect()
     }
@@ -166,30 +295,73 @@ impl DefCbmlFile {
     }
 
     fn parse_ast(&mut self, ast: Vec<Stmt>) {
-        // 先分成两部分, top level field def 和 type def.
+        self.stmts = ast.clone();
+
+        // 先分成三部分, top level field def, type def 和其他语句.
 
         let mut top_fields_def: Vec<Stmt> = Vec::new();
-        let mut type_def: Vec<Stmt> = Vec::new();
+        let mut type_names: Vec<String> = Vec::new();
+        let mut others: Vec<Stmt> = Vec::new();
 
         for s in ast {
-            match s.kind {
-                crate::parser::StmtKind::FileFieldStmt(_) => top_fields_def.push(s),
-                // crate::parser::StmtKind::TypeDef(_) => type_def.push(s),
-                _ => type_def.push(s),
+            let type_def = match s.kind {
+                crate::parser::StmtKind::FileFieldStmt(_) => {
+                    top_fields_def.push(s);
+                    continue;
+                }
+                crate::parser::StmtKind::StructDefStmt(struct_def) => {
+                    TypeDefStmt::StructDefStmt(struct_def)
+                }
+                crate::parser::StmtKind::EnumDef(enum_def) => TypeDefStmt::EnumDef(enum_def),
+                crate::parser::StmtKind::TypeDef(type_def_stmt) => type_def_stmt,
+                crate::parser::StmtKind::TypeAliasStmt(type_alias_stmt) => {
+                    TypeDefStmt::TypeAliasStmt(type_alias_stmt)
+                }
+                _ => {
+                    others.push(s);
+                    continue;
+                }
+            };
+
+            // 类型名重复检查.
+            let type_name = type_def.get_name().to_string();
+            if self._type_defs.contains_key(&type_name) {
+                let e = CbmlError::err_type_name_alredy_exits(
+                    self.file_path.clone(),
+                    type_def.get_span(),
+                    &type_name,
+                );
+                self.errors.push(e);
+                continue;
             }
 
-            // self.parse_one_stmt(s);
+            type_names.push(type_name.clone());
+            self._type_defs.insert(type_name, type_def);
         }
 
-        // 先解析类型定义
-        for x in type_def {
+        for x in others {
             self.parse_one_stmt(x);
         }
 
+        // 先解析类型定义, 按照定义的顺序解析, 使用到还未解析的类型时会先解析那个类型.
+        for x in type_names {
+            if let Some(type_def) = self._type_defs.remove(&x) {
+                self.parse_named_type_def(&x, type_def);
+            }
+        }
+
         // 在解析字段定义.
         for x in top_fields_def {
             self.parse_one_stmt(x);
         }
+
+        // 类型定义先解析, 子字段比父字段先记录, 按照在代码中的位置排序.
+        self.fields_map.sort_by(|_, a, _, b| {
+            a.span
+                .start
+                .character_index
+                .cmp(&b.span.start.character_index)
+        });
     }
 
     fn parse_one_stmt(&mut self, s: Stmt) {
@@ -203,7 +375,9 @@ impl DefCbmlFile {
             crate::parser::StmtKind::FileFieldStmt(struct_field_def_stmt) => {
                 self.parse_struct_field_def(struct_field_def_stmt);
             }
-            crate::parser::StmtKind::TypeAliasStmt(_) => {}
+            crate::parser::StmtKind::TypeAliasStmt(type_alias_stmt) => {
+                self.parse_type_alias_def(type_alias_stmt)
+            }
 
             crate::parser::StmtKind::StructDefStmt(struct_def) => self.parse_struct_def(struct_def),
 
@@ -249,9 +423,20 @@ impl DefCbmlFile {
         let span = struct_field_def_stmt.get_span();
         let type_sign_span = struct_field_def_stmt._type.span.clone();
 
+        // name: string default default
+        if let Some(default) = &struct_field_def_stmt.default {
+            if let LiteralKind::Default = default.kind {
+                let e = CbmlError::err_default_keyword_not_allowed_in_literal(
+                    self.file_path.clone(),
+                    default.span.clone(),
+                );
+                self.errors.push(e);
+            }
+        }
+
         let ty: CbmlType = self.parse_type_sign_stmt(
             struct_field_def_stmt._type,
-            &struct_field_def_stmt.field_name,
+            ScopeSegment::Field(struct_field_def_stmt.field_name.clone()),
         );
 
         let info = TypeInfo {
@@ -282,26 +467,23 @@ impl DefCbmlFile {
 
         let mut adsfsadf: Vec<(String, CbmlType)> = Vec::new();
 
-        self.into_scope(ScopeID::new(struct_def.struct_name.clone()));
+        let outer_scope = self.into_type_scope(&struct_def.struct_name);
         for x in struct_def.fields {
             let (field_name, field_type) = self.parse_struct_field_def(x);
             adsfsadf.push((field_name, field_type));
         }
-        self.outgoing_scope();
-
-        // let struct_type = CbmlType {
-        //     kind: CbmlTypeKind::Struct { fields: adsfsadf },
-        // };
-        // let asdf = self.gen_type_id();
-        // self.insert_type(
-        //     struct_def.struct_name.clone(),
-        //     TypeInfo {
-        //         name: struct_def.struct_name.clone(),
-        //         ty: struct_type,
-        //         span: struct_def.name_span.clone(),
-        //         type_id: asdf,
-        //     },
-        // );
+        self._current_scope = outer_scope;
+
+        let struct_type = CbmlType::Struct { fields: adsfsadf };
+        let type_id = self.gen_type_id();
+        self.types_map.insert(
+            struct_def.struct_name.clone(),
+            TypeInfo {
+                ty: struct_type,
+                span: struct_def.name_span.clone(),
+                type_id,
+            },
+        );
     }
 
     fn gen_type_id(&mut self) -> usize {
@@ -310,37 +492,41 @@ impl DefCbmlFile {
     }
 
     fn parse_enum_def(&mut self, enum_def: EnumDef) {
-        let mut adsfsadf: Vec<(String, CbmlType)> = Vec::new();
+        let mut adsfsadf: Vec<(String, Option<CbmlType>)> = Vec::new();
 
-        self.into_scope(ScopeID::new(enum_def.enum_name.clone()));
+        let outer_scope = self.into_type_scope(&enum_def.enum_name);
         for x in enum_def.fields {
-            let ty = self.parse_type_sign_stmt(x._type, &x.field_name);
-            adsfsadf.push((x.field_name.clone(), ty));
+            let sadf = self.parse_enum_field_def(x);
+            adsfsadf.push(sadf);
         }
-        self.outgoing_scope();
-
-        // let enum_type = CbmlType {
-        //     kind: CbmlTypeKind::Enum { fields: adsfsadf },
-        // };
-
-        // let sadf = self.gen_type_id();
-        // self.insert_type(
-        //     enum_def.enum_name.clone(),
-        //     TypeInfo {
-        //         name: enum_def.enum_name.clone(),
-        //         ty: enum_type,
-        //         span: enum_def.name_span.clone(),
-        //         type_id: sadf,
-        //     },
-        // );
+        self._current_scope = outer_scope;
+
+        let enum_type = CbmlType::Enum { fields: adsfsadf };
+        let type_id = self.gen_type_id();
+        self.types_map.insert(
+            enum_def.enum_name.clone(),
+            TypeInfo {
+                ty: enum_type,
+                span: enum_def.name_span.clone(),
+                type_id,
+            },
+        );
     }
 
-    fn parse_enum_field_def(&mut self, enum_field_def: EnumFieldDef) -> (String, CbmlType) {
+    fn parse_enum_field_def(&mut self, enum_field_def: EnumFieldDef) -> (String, Option<CbmlType>) {
+        // 没有值的成员不需要记录到 fields_map 中.
+        let Some(type_sign) = enum_field_def._type else {
+            return (enum_field_def.field_name, None);
+        };
+
+        // enum 可以选择其他的成员, 所以成员的类型可以是递归类型.
+        self._indirection_depth += 1;
         let ty = self.parse_type_sign_stmt(
-            enum_field_def._type,
-            &enum_field_def.field_name,
+            type_sign,
+            ScopeSegment::Variant(enum_field_def.field_name.clone()),
             // enum_field_def.field_name_span.clone(),
         );
+        self._indirection_depth -= 1;
 
         let info = TypeInfo {
             // name: type_name.clone(),
@@ -362,34 +548,149 @@ impl DefCbmlFile {
         let key = (field_def.name.clone(), self.get_current_scope_id());
         self.fields_map.insert(key, field_def);
 
-        return (enum_field_def.field_name.clone(), ty);
+        return (enum_field_def.field_name.clone(), Some(ty));
     }
 
     fn parse_union_def(&mut self, union_def: UnionDef) {
-        // let union_name = union_def.union_name.clone();
-        // let union_span = union_def.name_span.clone();
+        let union_name = union_def.union_name.clone();
+        let union_span = union_def.name_span.clone();
+
+        // union 中的值不能重复.
+        for x in union_def.duplicate_check() {
+            let e = CbmlError::err_union_duplicated_item(
+                self.file_path.clone(),
+                x.span.clone(),
+                &x.kind.to_cbml(0),
+            );
+            self.errors.push(e);
+        }
+
+        // union(string) 中的每一个值都需要是 string.
+        for x in &union_def.allowed_values {
+            let is_base_type = match &union_def.base_type {
+                TypeSignStmtKind::String { constraints } => match &x.kind {
+                    LiteralKind::String(s) => constraints.iter().all(|c| c.is_match(s)),
+                    _ => false,
+                },
+                TypeSignStmtKind::Number { range } => match x.kind {
+                    LiteralKind::Number(n) => range.as_ref().is_none_or(|r| r.contains(n)),
+                    _ => false,
+                },
+                TypeSignStmtKind::Int { range } => match x.kind {
+                    LiteralKind::Number(n) => {
+                        n.fract() == 0.0 && range.as_ref().is_none_or(|r| r.contains(n))
+                    }
+                    _ => false,
+                },
+                TypeSignStmtKind::Boolean => matches!(x.kind, LiteralKind::Boolean(_)),
+                // any 和其他类型不限制.
+                _ => true,
+            };
+
+            if !is_base_type {
+                let e = CbmlError::err_mismatched_types(
+                    self.file_path.clone(),
+                    x.span.clone(),
+                    &union_name,
+                    &union_def.base_type.to_cbml(0),
+                    &x.kind.to_cbml(0),
+                );
+                self.errors.push(e);
+            }
+        }
 
         let mut alowd_values: Vec<CbmlValue> = Vec::new();
         for x in &union_def.allowed_values {
             alowd_values.push(x.to_cbml_value());
         }
 
-        // let union_type = CbmlType {
-        //     kind: CbmlTypeKind::Union {
-        //         allowed_values: alowd_values,
-        //     },
-        // };
+        let union_type = CbmlType::Union {
+            allowed_values: alowd_values,
+        };
 
-        // let sadf = self.gen_type_id();
-        // self.insert_type(
-        //     union_name.clone(),
-        //     TypeInfo {
-        //         name: union_name.clone(),
-        //         ty: union_type.clone(),
-        //         span: union_span,
-        //         type_id: sadf,
-        //     },
-        // );
+        let type_id = self.gen_type_id();
+        self.types_map.insert(
+            union_name,
+            TypeInfo {
+                ty: union_type,
+                span: union_span,
+                type_id,
+            },
+        );
+    }
+
+    /// type name = type_sign
+    fn parse_type_alias_def(&mut self, type_alias_stmt: TypeAliasStmt) {
+        // 别名的子字段定义在 `[Type(别名)]` 中, 跟具名类型一样.
+        let outer_scope = std::mem::take(&mut self._current_scope);
+        let ty = self.parse_type_sign_stmt(
+            type_alias_stmt.ty,
+            ScopeSegment::Type(type_alias_stmt.name.clone()),
+        );
+        self._current_scope = outer_scope;
+
+        let type_id = self.gen_type_id();
+        self.types_map.insert(
+            type_alias_stmt.name,
+            TypeInfo {
+                ty,
+                span: type_alias_stmt.name_span,
+                type_id,
+            },
+        );
+    }
+
+    fn parse_named_type_def(&mut self, type_name: &str, type_def: TypeDefStmt) {
+        let is_alias = matches!(type_def, TypeDefStmt::TypeAliasStmt(_));
+
+        self._resolving_types
+            .push((type_name.to_string(), is_alias, self._indirection_depth));
+        self.parse_type_def(type_def);
+        self._resolving_types.pop();
+    }
+
+    /// 获取具名类型, 如果这个类型还未解析, 则先解析这个类型.
+    fn resolve_named_type(&mut self, type_name: &str, span: Span) -> CbmlType {
+        if let Some(info) = self.types_map.get(type_name) {
+            return info.ty.clone();
+        }
+
+        if let Some(index) = self._resolving_types.iter().position(|x| x.0 == type_name) {
+            let cycle = &self._resolving_types[index..];
+
+            // 只由 type alias 组成的循环无法展开.
+            let e = if cycle.iter().all(|x| x.1) {
+                let mut names: Vec<&str> = cycle.iter().map(|x| x.0.as_str()).collect();
+                names.push(type_name);
+
+                CbmlError::err_type_alias_cycle(self.file_path.clone(), span, &names)
+            } else if self._indirection_depth > cycle[0].2 {
+                // 递归类型, 类型检查时再展开.
+                return CbmlType::Named {
+                    type_name: type_name.to_string(),
+                };
+            } else {
+                CbmlError::err_recursive_type(self.file_path.clone(), span, type_name)
+            };
+            self.errors.push(e);
+
+            return CbmlType::Any;
+        }
+
+        let Some(type_def) = self._type_defs.remove(type_name) else {
+            let e = CbmlError::err_cannot_find_type(self.file_path.clone(), span, type_name);
+            self.errors.push(e);
+
+            return CbmlType::Any;
+        };
+
+        self.parse_named_type_def(type_name, type_def);
+
+        return self
+            .types_map
+            .get(type_name)
+            .map(|x| x.ty.clone())
+            .unwrap_or(CbmlType::Any);
     }
 
     // return: (type_name, CbmlTYpe)
@@ -397,7 +698,8 @@ impl DefCbmlFile {
         &mut self,
         // sign: TypeSignStmtKind,
         sign: TypeSignStmt,
-        field_name: &str,
+        // 这个类型的子字段所在的 scope 的最后一段.
+        segment: ScopeSegment,
         // span: Span,
         // ) -> (String, CbmlType) {
     ) -> CbmlType {
@@ -405,27 +707,58 @@ impl DefCbmlFile {
         let span = sign.span;
 
         match sign.kind {
-            crate::parser::ast::stmt::TypeSignStmtKind::String => CbmlType::String,
-            crate::parser::ast::stmt::TypeSignStmtKind::Number => CbmlType::Number,
+            crate::parser::ast::stmt::TypeSignStmtKind::String { constraints } => {
+                self.check_string_constraints(&constraints, &span);
+                CbmlType::String { constraints }
+            }
+            crate::parser::ast::stmt::TypeSignStmtKind::Number { range } => {
+                self.check_number_range(range.as_ref(), &span);
+                CbmlType::Number { range }
+            }
+            crate::parser::ast::stmt::TypeSignStmtKind::Int { range } => {
+                self.check_number_range(range.as_ref(), &span);
+                CbmlType::Int { range }
+            }
             crate::parser::ast::stmt::TypeSignStmtKind::Boolean => CbmlType::Bool,
             crate::parser::ast::stmt::TypeSignStmtKind::Any => CbmlType::Any,
             crate::parser::ast::stmt::TypeSignStmtKind::Anonymous(anonymous_type_def_stmt) => {
-                let a = self.parse_anonymous_type_def_stmt(anonymous_type_def_stmt, field_name);
+                let a = self.parse_anonymous_type_def_stmt(anonymous_type_def_stmt, segment);
                 return a;
             }
-            crate::parser::ast::stmt::TypeSignStmtKind::Custom(_custom_type_namee) => {
-                //
-                let e = CbmlError {
-                    error_code: 0000,
-                    file_path: self.file_path.clone(),
-                    msg: format!("unkonw type: {}", _custom_type_namee),
-                    span,
-                    note: None,
-                    help: None,
-                };
-                self.errors.push(e);
+            crate::parser::ast::stmt::TypeSignStmtKind::Custom(custom_type_name) => {
+                let ty = self.resolve_named_type(&custom_type_name, span);
+
+                // 这个字段的子字段定义在具名类型的 scope 中.
+                let child_scope = self.get_current_scope_id().join(segment);
+                let type_scope = ScopeID::new(vec![ScopeSegment::Type(custom_type_name)]);
+                let aliases = self.scope_alias.entry(child_scope).or_default();
+                if !aliases.contains(&type_scope) {
+                    aliases.push(type_scope);
+                }
+
+                return ty;
+            }
+        }
+    }
+
+    /// `int(10..1)` 这样的范围中没有任何值.
+    fn check_number_range(&mut self, range: Option<&NumberRange>, span: &Span) {
+        if let Some(range) = range.filter(|x| x.is_empty()) {
+            let e = CbmlError::err_empty_number_range(
+                self.file_path.clone(),
+                span.clone(),
+                &range.to_string(),
+            );
+            self.errors.push(e);
+        }
+    }
 
-                return CbmlType::Any;
+    /// 长度的范围不能是空的.
+    /// 无效的正则表达式在解析的时候已经报错了.
+    fn check_string_constraints(&mut self, constraints: &[StringConstraint], span: &Span) {
+        for x in constraints {
+            if let StringConstraint::Length(range) = x {
+                self.check_number_range(Some(range), span);
             }
         }
     }
@@ -434,29 +767,51 @@ impl DefCbmlFile {
     fn parse_anonymous_type_def_stmt(
         &mut self,
         anonymous_type_def_stmt: AnonymousTypeDefStmt,
-        field_name: &str,
+        segment: ScopeSegment,
     ) -> CbmlType {
         // let anony_span = anonymous_type_def_stmt.span.clone();
 
         match anonymous_type_def_stmt.kind {
-            crate::parser::ast::stmt::AnonymousTypeDefKind::Array { inner_type } => {
+            crate::parser::ast::stmt::AnonymousTypeDefKind::Array {
+                inner_type,
+                constraints,
+            } => {
+                for x in &constraints {
+                    if let ArrayConstraint::Length(range) = x {
+                        self.check_number_range(Some(range), &anonymous_type_def_stmt.span);
+                    }
+                }
+
+                // `[Node](1..)` 不能是空数组, 所以不能用来包裹递归类型.
+                let can_be_empty = constraints.iter().all(|x| match x {
+                    ArrayConstraint::Length(range) => range.contains(0.0),
+                    ArrayConstraint::Unique => true,
+                });
+
+                if can_be_empty {
+                    self._indirection_depth += 1;
+                }
                 let ty = self.parse_type_sign_stmt(
                     *inner_type,
                     // &format!("{}{}", field_name, "array_inner"),
-                    field_name,
+                    segment,
                     // anony_span.clone(),
                 );
+                if can_be_empty {
+                    self._indirection_depth -= 1;
+                }
 
                 let array_type = CbmlType::Array {
                     inner_type: ty.clone().into(),
+                    constraints,
                 };
 
                 return array_type;
             }
             crate::parser::ast::stmt::AnonymousTypeDefKind::Enum { fields } => {
-                let mut fieasdfasflds: Vec<(String, CbmlType)> = Vec::new();
+                let mut fieasdfasflds: Vec<(String, Option<CbmlType>)> = Vec::new();
 
-                self.into_scope(ScopeID::new(field_name.to_string()));
+                self.into_scope(segment);
                 for x in fields {
                     let sadf = self.parse_enum_field_def(x);
                     fieasdfasflds.push(sadf);
@@ -470,13 +825,11 @@ impl DefCbmlFile {
             crate::parser::ast::stmt::AnonymousTypeDefKind::Struct(struct_field_def_stmts) => {
                 let mut adsfsadf: Vec<(String, CbmlType)> = Vec::new();
 
-                self.into_scope(ScopeID::new(field_name.to_string()));
+                self.into_scope(segment);
                 for x in struct_field_def_stmts {
-                    let ty = self.parse_type_sign_stmt(x._type.clone(), &x.field_name);
-                    adsfsadf.push((x.field_name.clone(), ty));
-
                     // 存储字段.
-                    _ = self.parse_struct_field_def(x);
+                    let sadf = self.parse_struct_field_def(x);
+                    adsfsadf.push(sadf);
                 }
                 self.outgoing_scope();
 
@@ -494,12 +847,67 @@ impl DefCbmlFile {
 
                 return union_type;
             }
+            crate::parser::ast::stmt::AnonymousTypeDefKind::TypeUnion { types } => {
+                // 每一个类型的子字段都在同一个 scope 中, 查找字段定义时会查找所有的类型.
+                // 其中一个类型可以是递归的, 比如 `next: Node | string`.
+                self._indirection_depth += 1;
+
+                let mut union_types: Vec<CbmlType> = Vec::new();
+                for x in types {
+                    let type_name = match &x.kind {
+                        TypeSignStmtKind::Custom(name) => Some(name.clone()),
+                        _ => None,
+                    };
+
+                    let ty = self.parse_type_sign_stmt(x, segment.clone());
+
+                    // 具名类型保留名字, 报错时显示 `Git | Dir` 而不是展开后的结构体.
+                    match type_name {
+                        Some(type_name) => union_types.push(CbmlType::Named { type_name }),
+                        None => union_types.push(ty),
+                    }
+                }
+
+                self._indirection_depth -= 1;
+
+                return CbmlType::TypeUnion { types: union_types };
+            }
+            crate::parser::ast::stmt::AnonymousTypeDefKind::Map {
+                key_type,
+                value_type,
+            } => {
+                if !matches!(key_type.kind, TypeSignStmtKind::String { .. }) {
+                    let e = CbmlError::err_map_key_must_be_string(
+                        self.file_path.clone(),
+                        key_type.span.clone(),
+                        &key_type.to_cbml(0),
+                    );
+                    self.errors.push(e);
+                }
+
+                // 空的 map 是有限的, 所以 map 的值可以是递归类型.
+                self._indirection_depth += 1;
+                let ty = self.parse_type_sign_stmt(*value_type, segment.clone());
+                self._indirection_depth -= 1;
+
+                // 值的子字段跟数组一样定义在这个字段的子 scope 中.
+                let map_scope = self.get_current_scope_id().join(segment);
+                let keys = 1 + map_depth(&ty);
+                let entry = self.map_scopes.entry(map_scope).or_default();
+                *entry = (*entry).max(keys);
+
+                return CbmlType::Map {
+                    value_type: Box::new(ty),
+                };
+            }
             crate::parser::ast::stmt::AnonymousTypeDefKind::Optional { inner_type } => {
+                self._indirection_depth += 1;
                 let ty = self.parse_type_sign_stmt(
                     *inner_type,
-                    &format!("{}_{}", field_name, "optional"),
+                    segment,
                     // anony_span.clone(),
                 );
+                self._indirection_depth -= 1;
 
                 let optional_type = CbmlType::Optional {
                     inner_type: ty.clone().into(),
@@ -517,6 +925,9 @@ impl DefCbmlFile {
             TypeDefStmt::StructDefStmt(struct_def) => self.parse_struct_def(struct_def),
             TypeDefStmt::EnumDef(enum_def) => self.parse_enum_def(enum_def),
             TypeDefStmt::UnionDef(union_def) => self.parse_union_def(union_def),
+            TypeDefStmt::TypeAliasStmt(type_alias_stmt) => {
+                self.parse_type_alias_def(type_alias_stmt)
+            }
         }
     }
 }
@@ -524,31 +935,51 @@ impl DefCbmlFile {
 /// scope 相关的函数.
 impl DefCbmlFile {
     fn get_current_scope_id(&self) -> ScopeID {
-        let mut re = String::new();
-
-        for x in &self._current_scope {
-            re.push_str("::");
-            re.push_str(&x.0);
-        }
-
-        return ScopeID::new(re);
+        return self._current_scope.clone();
     }
 
-    fn into_scope(&mut self, scope_id: ScopeID) {
-        self._current_scope.push(scope_id);
+    fn into_scope(&mut self, segment: ScopeSegment) {
+        self._current_scope.push(segment);
     }
 
     fn outgoing_scope(&mut self) {
         let _ = self._current_scope.pop();
     }
+
+    /// 具名类型的字段定义在 `[Type(类型名)]` 中, 与使用这个类型的位置无关.
+    /// return: 进入之前的 scope.
+    fn into_type_scope(&mut self, type_name: &str) -> ScopeID {
+        let outer_scope = std::mem::take(&mut self._current_scope);
+        self.into_scope(ScopeSegment::Type(type_name.to_string()));
+
+        return outer_scope;
+    }
+}
+
+/// 跟 map 在同一个 scope 中的嵌套 map 的层数, `{ [string]: [{ [string]: number }] }` 是 1.
+/// 具名类型有自己的 scope, 所以不算在内.
+fn map_depth(ty: &CbmlType) -> usize {
+    match ty {
+        CbmlType::Map { value_type } => 1 + map_depth(value_type),
+        CbmlType::Array { inner_type, .. } | CbmlType::Optional { inner_type } => {
+            map_depth(inner_type)
+        }
+        CbmlType::TypeUnion { types } => types.iter().map(map_depth).max().unwrap_or(0),
+        _ => 0,
+    }
 }
 
 impl ToCbml for DefCbmlFile {
     fn to_cbml(&self, deepth: usize) -> String {
         let mut re = String::new();
 
-        for (_, x) in &self.fields_map {
-            re.push_str(&x.to_cbml(deepth));
+        // 按照在代码中的顺序输出字段定义和类型定义, 具名类型保持原来的写法.
+        for x in &self.stmts {
+            if matches!(x.kind, StmtKind::EmptyLine) {
+                continue;
+            }
+
+            re.push_str(x.to_cbml(deepth).trim_end());
             re.push_str("\n");
         }
 
@@ -562,3 +993,408 @@ impl DefCbmlFile {
         todo!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cbml_project::cbml_file::CbmlFile;
+    use crate::cbml_project::test_utils::*;
+
+    fn type_scope(type_name: &str) -> ScopeID {
+        ScopeID::new(vec![ScopeSegment::Type(type_name.into())])
+    }
+
+    #[test]
+    fn test_named_types() {
+        let code = r##"
+who: Person
+loc: location
+name: names
+
+/// 人
+struct Person {
+    name: string
+    address: Address
+}
+
+struct Address {
+    city: string
+}
+
+enum location {
+    git({
+        url: string
+        branch: string
+    })
+    directory(Address)
+}
+
+union(string) names = | "hello" | "alex"
+"##;
+
+        let f = new_def_file(code);
+        assert!(f.errors.is_empty(), "{:?}", f.errors);
+
+        let address = CbmlType::Struct {
+            fields: vec![(
+                "city".into(),
+                CbmlType::String {
+                    constraints: vec![],
+                },
+            )],
+        };
+        assert_eq!(f.get_type_by_name("Address").unwrap().ty, address);
+        assert_eq!(
+            f.get_type_by_name("Person").unwrap().ty,
+            CbmlType::Struct {
+                fields: vec![
+                    (
+                        "name".into(),
+                        CbmlType::String {
+                            constraints: vec![]
+                        }
+                    ),
+                    ("address".into(), address)
+                ],
+            }
+        );
+
+        let who = f
+            .get_field_def_by_name("who".into(), ScopeID::empty())
+            .unwrap();
+        assert_eq!(who.type_.ty, f.get_type_by_name("Person").unwrap().ty);
+
+        // 具名类型的字段通过使用它的字段查找.
+        let city = f.get_field_def_by_name(
+            "city".into(),
+            ScopeID::empty().child("who").child("address"),
+        );
+        assert_eq!(city.unwrap().scope_id, type_scope("Address"));
+
+        let url =
+            f.get_field_def_by_name("url".into(), ScopeID::empty().child("loc").variant("git"));
+        assert_eq!(
+            url.unwrap().type_.ty,
+            CbmlType::String {
+                constraints: vec![]
+            }
+        );
+
+        let data = r##"
+who = { name = "a", address = { city = "b" } }
+loc = directory({ city = "c" })
+name = "alex"
+"##;
+        let data_file = CbmlFile::new_from_with_typedef("test.cbml".into(), data, f.clone());
+        assert!(data_file.errors.is_empty(), "{:?}", data_file.errors);
+
+        let data = r##"
+who = { name = "a", address = { city = 1 } }
+loc = git({ url = "c", branch = "main" })
+name = "bob"
+"##;
+        let data_file = CbmlFile::new_from_with_typedef("test.cbml".into(), data, f);
+        assert!(!data_file.errors.is_empty());
+        assert!(data_file.errors.iter().all(|x| x.error_code == 4));
+        assert!(data_file.errors.iter().any(|x| x.msg.contains("\"bob\"")));
+    }
+
+    #[test]
+    fn test_named_type_errors() {
+        let code = r##"
+a: Unknown
+struct A { x: number }
+enum A {
+    b(string)
+}
+"##;
+        let f = new_def_file(code);
+        assert_eq!(
+            error_details(code, &f.errors),
+            vec![
+                (9, "type name `A` alredy exit".into(), "A".into()),
+                (2, "connot find type `Unknown` ".into(), "a: Unknown".into()),
+            ]
+        );
+        assert_eq!(f.errors[0].span.start.line, 3);
+
+        let code = r##"
+a: A
+struct A { b: B }
+struct B { a: A }
+"##;
+        let f = new_def_file(code);
+        assert_eq!(
+            error_details(code, &f.errors),
+            vec![(
+                22,
+                "recursive type `A` has infinite size".into(),
+                "a: A".into()
+            )]
+        );
+        assert_eq!(
+            f.errors[0].note.as_deref(),
+            Some("类型 `A` 直接或间接的引用了它自己.")
+        );
+    }
+
+    #[test]
+    fn test_recursive_types() {
+        let code = r##"
+root: Node
+tree: Tree
+
+struct Node {
+    name: string
+    children: [Node]
+    next: ?Node
+}
+
+enum Tree {
+    leaf(number)
+    node(Branch)
+}
+
+type Branch = {
+    left: Tree
+    right: Tree
+}
+"##;
+        let f = new_def_file(code);
+        assert!(f.errors.is_empty(), "{:?}", f.errors);
+
+        let node = CbmlType::Named {
+            type_name: "Node".into(),
+        };
+        assert_eq!(
+            f.get_type_by_name("Node").unwrap().ty,
+            CbmlType::Struct {
+                fields: vec![
+                    (
+                        "name".into(),
+                        CbmlType::String {
+                            constraints: vec![]
+                        }
+                    ),
+                    (
+                        "children".into(),
+                        CbmlType::Array {
+                            inner_type: Box::new(node.clone()),
+                            constraints: vec![],
+                        }
+                    ),
+                    (
+                        "next".into(),
+                        CbmlType::Optional {
+                            inner_type: Box::new(node),
+                        }
+                    ),
+                ],
+            }
+        );
+
+        let name = f.get_field_def_by_name(
+            "name".into(),
+            ScopeID::empty()
+                .child("root")
+                .child("children")
+                .index(0)
+                .child("next")
+                .child("children"),
+        );
+        assert_eq!(name.unwrap().scope_id, type_scope("Node"));
+
+        let data = r##"
+root = {
+    name = "a"
+    children = [{
+        name = "b"
+        children = []
+        next = { name = "c", children = [], next = none }
+    }]
+    next = none
+}
+tree = node({
+    left = leaf(1)
+    right = node({ left = leaf(2), right = leaf(3) })
+})
+"##;
+        let data_file = CbmlFile::new_from_with_typedef("test.cbml".into(), data, f.clone());
+        assert!(data_file.errors.is_empty(), "{:?}", data_file.errors);
+
+        let data = r##"
+root = { name = "a", children = [], next = { name = 1, children = [], next = none } }
+tree = node({ left = leaf(1), right = node({ left = leaf("2"), right = leaf(3) }) })
+"##;
+        let data_file = CbmlFile::new_from_with_typedef("test.cbml".into(), data, f);
+        assert_eq!(data_file.errors.len(), 2, "{:?}", data_file.errors);
+        assert!(data_file.errors.iter().all(|x| x.error_code == 4));
+        assert!(data_file.errors[0].msg.contains("`root.next.name`"));
+        assert!(
+            data_file.errors[1]
+                .msg
+                .contains("`tree.node.right.node.left.leaf`")
+        );
+    }
+
+    #[test]
+    fn test_type_alias() {
+        let code = r##"
+name: names
+who: P
+people: People
+
+type names =
+    | "hello"
+    | "alex"
+type People = [P]
+type P = Person
+/// 人
+type Person = {
+    name: names
+}
+"##;
+        let f = new_def_file(code);
+        assert!(f.errors.is_empty(), "{:?}", f.errors);
+
+        let names = CbmlType::Union {
+            allowed_values: vec![
+                CbmlValue::String("hello".into()),
+                CbmlValue::String("alex".into()),
+            ],
+        };
+        let person = CbmlType::Struct {
+            fields: vec![("name".into(), names.clone())],
+        };
+        assert_eq!(f.get_type_by_name("names").unwrap().ty, names);
+        assert_eq!(f.get_type_by_name("P").unwrap().ty, person);
+        assert_eq!(
+            f.get_type_by_name("People").unwrap().ty,
+            CbmlType::Array {
+                inner_type: Box::new(person),
+                constraints: vec![],
+            }
+        );
+
+        let name =
+            f.get_field_def_by_name("name".into(), ScopeID::empty().child("people").index(0));
+        assert_eq!(name.unwrap().scope_id, type_scope("Person"));
+
+        let data = r##"
+name = "alex"
+who = { name = "hello" }
+people = [{ name = "alex" }]
+"##;
+        let data_file = CbmlFile::new_from_with_typedef("test.cbml".into(), data, f.clone());
+        assert!(data_file.errors.is_empty(), "{:?}", data_file.errors);
+
+        let data = r##"
+name = "alex"
+who = { name = "bob" }
+people = []
+"##;
+        let data_file = CbmlFile::new_from_with_typedef("test.cbml".into(), data, f);
+        assert!(!data_file.errors.is_empty());
+        assert!(data_file.errors.iter().all(|x| x.error_code == 4));
+    }
+
+    #[test]
+    fn test_type_alias_cycle() {
+        let code = r##"
+a: A
+type A = B
+type B = ?A
+"##;
+        let f = new_def_file(code);
+        let msg = "cycle detected when expanding type alias `A`";
+        assert_eq!(
+            error_details(code, &f.errors),
+            vec![(23, msg.into(), "A".into())]
+        );
+        assert_eq!(f.errors[0].span.start.line, 3);
+        assert_eq!(f.errors[0].note, Some("A -> B -> A".into()));
+
+        let code = r##"
+type A = A
+"##;
+        let f = new_def_file(code);
+        assert_eq!(
+            error_details(code, &f.errors),
+            vec![(23, msg.into(), "A".into())]
+        );
+        assert_eq!(f.errors[0].note, Some("A -> A".into()));
+
+        let code = r##"
+type A = Unknown
+type A = string
+"##;
+        let f = new_def_file(code);
+        assert_eq!(
+            error_details(code, &f.errors),
+            vec![
+                (9, "type name `A` alredy exit".into(), "A".into()),
+                (2, "connot find type `Unknown` ".into(), "Unknown".into()),
+            ]
+        );
+        assert_eq!(f.errors[0].span.start.line, 2);
+    }
+
+    #[test]
+    fn test_named_union() {
+        let code = r##"
+name: names
+nick: ?names
+
+union(string) names {
+    "hello" | "alex" | "张三"
+}
+"##;
+        let f = new_def_file(code);
+        assert!(f.errors.is_empty(), "{:?}", f.errors);
+
+        let data_file = CbmlFile::new_from_with_typedef(
+            "test.cbml".into(),
+            "name = \"alex\"\nnick = none",
+            f.clone(),
+        );
+        assert!(data_file.errors.is_empty(), "{:?}", data_file.errors);
+
+        let data_file =
+            CbmlFile::new_from_with_typedef("test.cbml".into(), "name = \"bob\"\nnick = \"a\"", f);
+        assert_eq!(data_file.errors.len(), 2);
+        for x in &data_file.errors {
+            assert_eq!(x.error_code, 4);
+            assert_eq!(
+                x.help.as_deref(),
+                Some("可以使用的值: \"hello\" | \"alex\" | \"张三\"")
+            );
+        }
+
+        let code = r##"
+union(string) names = | "hello" | 1 | "hello" | true
+"##;
+        let f = new_def_file(code);
+        assert_eq!(
+            error_details(code, &f.errors),
+            vec![
+                (
+                    5,
+                    "union duplicated item: \"hello\"".into(),
+                    "\"hello\"".into()
+                ),
+                (
+                    4,
+                    "mismatched types at `names`, expected `string` found `1`".into(),
+                    "1".into()
+                ),
+                (
+                    4,
+                    "mismatched types at `names`, expected `string` found `true`".into(),
+                    "true".into()
+                ),
+            ]
+        );
+        // 重复的是后面的那一个.
+        assert_eq!(f.errors[0].span.start.character_index, 39);
+    }
+}
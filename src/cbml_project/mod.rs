@@ -2,3 +2,6 @@
 pub mod cbml_file;
 pub mod def_cbml_file;
 pub mod types;
+
+#[cfg(test)]
+mod test_utils;
@@ -0,0 +1,35 @@
+//! cbml_file 和 def_cbml_file 的测试共用的函数.
+
+use super::cbml_file::CbmlFile;
+use super::def_cbml_file::DefCbmlFile;
+use crate::lexer::token::Span;
+use crate::parser::parser_error::CbmlError;
+
+/// 解析 .def.cbml 和使用它的 .cbml, .def.cbml 中不能有错误.
+pub fn new_file(def_code: &str, code: &str) -> CbmlFile {
+    let def_file = new_def_file(def_code);
+    assert!(def_file.errors.is_empty(), "{:?}", def_file.errors);
+
+    return CbmlFile::new_from_with_typedef("test.cbml".into(), code, def_file);
+}
+
+pub fn new_def_file(def_code: &str) -> DefCbmlFile {
+    return DefCbmlFile::new_from("test.def.cbml".into(), def_code);
+}
+
+/// 错误的代码, 信息和位置上的代码, 例如:
+/// (4, "mismatched types at `list[1]`, expected `number` found `\"2\"`", "\"2\"")
+pub fn error_details(code: &str, errors: &[CbmlError]) -> Vec<(u32, String, String)> {
+    return errors
+        .iter()
+        .map(|x| (x.error_code, x.msg.clone(), span_text(code, &x.span)))
+        .collect();
+}
+
+/// span 中的代码, span 是按字符计算的.
+pub fn span_text(code: &str, span: &Span) -> String {
+    let start = span.start.character_index;
+    let end = span.end.character_index;
+
+    return code.chars().skip(start).take(end - start).collect();
+}
@@ -17,14 +17,7 @@ pub struct FieldAsign {
 
 impl FieldAsign {
     pub fn child_scope(&self) -> ScopeID {
-        // let asdf = vec![self.scope.clone(), ScopeID::new(self.name.clone())];
-
-        let mut re = String::new();
-        re.push_str(&self.scope.0);
-        re.push_str("::");
-        re.push_str(&self.name);
-
-        return ScopeID::new(re);
+        return self.scope.child(&self.name);
     }
 }
 
@@ -40,15 +33,17 @@ pub struct FieldDef {
 }
 
 impl FieldDef {
-    pub fn child_scope(&self) -> ScopeID {
-        // let asdf = vec![self.scope.clone(), ScopeID::new(self.name.clone())];
+    /// 可选类型和有默认值的字段可以不赋值.
+    pub fn can_be_omitted(&self) -> bool {
+        if self.default_value.is_some() {
+            return true;
+        }
 
-        let mut re = String::new();
-        re.push_str(&self.scope_id.0);
-        re.push_str("::");
-        re.push_str(&self.name);
+        return matches!(self.type_.ty, CbmlType::Optional { .. });
+    }
 
-        return ScopeID::new(re);
+    pub fn child_scope(&self) -> ScopeID {
+        return self.scope_id.child(&self.name);
     }
 }
 
@@ -68,9 +63,12 @@ impl ToCbml for FieldDef {
     fn to_cbml(&self, deepth: usize) -> String {
         let mut re = String::new();
 
+        // doc 中保存的是去掉了 `///` 的内容.
         if let Some(doc) = &self.doc {
-            re.push_str(&"    ".repeat(deepth));
-            re.push_str(&format!("{}", doc));
+            for line in doc.lines() {
+                re.push_str(&"    ".repeat(deepth));
+                re.push_str(&format!("///{}\n", line));
+            }
         }
 
         re.push_str(&"    ".repeat(deepth));
@@ -100,8 +98,8 @@ pub struct TypeInfo {
 impl TypeInfo {
     pub fn get_type_id(&self) -> usize {
         match &self.ty {
-            CbmlType::String => 0,
-            CbmlType::Number => 1,
+            CbmlType::String { constraints } if constraints.is_empty() => 0,
+            CbmlType::Number { range: None } => 1,
             CbmlType::Bool => 2,
             CbmlType::Any => 3,
             _ => self.type_id,
@@ -109,15 +107,89 @@ impl TypeInfo {
     }
 }
 
+/// scope 路径中的一段.
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
-pub struct ScopeID(pub String);
+pub enum ScopeSegment {
+    /// 结构体的字段, `a = { b = 1 }` 中的 `b`.
+    Field(String),
+
+    /// 数组的元素, `a = [{ b = 1 }]` 中的 `[0]`.
+    /// 字段定义中没有数组下标, 查找字段定义时会忽略这一段.
+    Index(usize),
+
+    /// enum 的成员, `a = git({ url = "" })` 中的 `git`.
+    Variant(String),
+
+    /// 具名类型, 具名类型中的字段定义在这个 scope 中, 跟使用这个类型的位置无关.
+    Type(String),
+}
+
+/// 字段所在的位置, 顶级字段的 scope 是空的.
+/// `a = [{ b = 1 }]` 中 `b` 的 scope 是 `[Field("a"), Index(0)]`.
+#[derive(Debug, Clone, PartialEq, Hash, Eq, Default)]
+pub struct ScopeID(pub Vec<ScopeSegment>);
 
 impl ScopeID {
-    pub fn new(scope: String) -> Self {
-        Self(scope)
+    pub fn new(segments: Vec<ScopeSegment>) -> Self {
+        Self(segments)
     }
 
     pub fn empty() -> Self {
-        Self::new(String::new())
+        Self::new(Vec::new())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn push(&mut self, segment: ScopeSegment) {
+        self.0.push(segment);
+    }
+
+    pub fn pop(&mut self) -> Option<ScopeSegment> {
+        self.0.pop()
+    }
+
+    /// 在后面添加一段, 返回新的 scope.
+    pub fn join(&self, segment: ScopeSegment) -> Self {
+        let mut re = self.clone();
+        re.push(segment);
+        return re;
+    }
+
+    /// `a` -> `a.b`
+    pub fn child(&self, name: &str) -> Self {
+        self.join(ScopeSegment::Field(name.to_string()))
+    }
+
+    /// `a` -> `a[0]`
+    pub fn index(&self, index: usize) -> Self {
+        self.join(ScopeSegment::Index(index))
+    }
+
+    /// `a` -> `a.git`
+    pub fn variant(&self, name: &str) -> Self {
+        self.join(ScopeSegment::Variant(name.to_string()))
+    }
+}
+
+/// 显示为字段的路径, 例如 `a.b[0].git`.
+impl std::fmt::Display for ScopeID {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, x) in self.0.iter().enumerate() {
+            match x {
+                ScopeSegment::Index(index) => write!(f, "[{}]", index)?,
+                ScopeSegment::Field(name)
+                | ScopeSegment::Variant(name)
+                | ScopeSegment::Type(name) => {
+                    if i != 0 {
+                        write!(f, ".")?;
+                    }
+                    write!(f, "{}", name)?;
+                }
+            }
+        }
+
+        return Ok(());
     }
 }
@@ -0,0 +1,721 @@
+use super::error::Error;
+use super::spanned;
+use crate::cbml_data::cbml_value::CbmlValue;
+use crate::cbml_project::cbml_file::CbmlFile;
+use crate::cbml_project::types::ScopeID;
+use crate::lexer::token::{Position, Span};
+use serde::Deserialize;
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    Unexpected, VariantAccess, Visitor,
+};
+use std::collections::HashMap;
+
+/// 将 .cbml 代码转换为 T.
+/// 代码中有 `use` 语句时会先做类型检查, 并填充省略了的字段.
+/// 无法转换为 T 时, 错误是带有代码位置的 Error::Cbml, 可以用 Error::report_error 输出.
+/// ```
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     name: String,
+///     port: u16,
+/// }
+///
+/// let c: Config = cbml::from_str("name = \"cbml\"\nport = 8080").unwrap();
+/// assert_eq!(c.port, 8080);
+/// ```
+pub fn from_str<T: DeserializeOwned>(code: &str) -> Result<T, Error> {
+    let f = CbmlFile::new_from(String::new(), code);
+    if !f.errors.is_empty() {
+        return Err(Error::Cbml(f.errors));
+    }
+
+    return f.deserialize();
+}
+
+/// 将 CbmlValue 转换为 T.
+pub fn from_value<'de, T: Deserialize<'de>>(value: &'de CbmlValue) -> Result<T, Error> {
+    return T::deserialize(Deserializer::new(value));
+}
+
+/// 可以直接从 CbmlFile 转换, 错误会指向 CbmlFile 中的代码.
+impl CbmlFile {
+    pub fn deserialize<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        let value = self.try_to_cbml_value().map_err(Error::Cbml)?;
+        let spans = self.literal_spans();
+
+        let de = Deserializer {
+            value: &value,
+            path: ScopeID::empty(),
+            span: None,
+            spans: Some(&spans),
+        };
+
+        return T::deserialize(de).map_err(|e| e.with_file_path(&self.file_path));
+    }
+}
+
+/// 从 CbmlValue 读取数据的 serde::Deserializer.
+pub struct Deserializer<'de> {
+    value: &'de CbmlValue,
+
+    /// value 的路径, 用于错误信息.
+    path: ScopeID,
+
+    /// value 在代码中的位置, 省略了的字段使用所在的结构体的位置.
+    span: Option<Span>,
+
+    /// 每一个值在代码中的位置, from_value 时是 None.
+    spans: Option<&'de HashMap<ScopeID, Span>>,
+}
+
+impl<'de> Deserializer<'de> {
+    pub fn new(value: &'de CbmlValue) -> Self {
+        Self {
+            value,
+            path: ScopeID::empty(),
+            span: None,
+            spans: None,
+        }
+    }
+
+    /// value 中的子字段, 数组元素, enum 成员的值.
+    fn child(&self, value: &'de CbmlValue, path: ScopeID) -> Self {
+        let span = self
+            .spans
+            .and_then(|x| x.get(&path))
+            .cloned()
+            .or_else(|| self.span.clone());
+
+        Self {
+            value,
+            path,
+            span,
+            spans: self.spans,
+        }
+    }
+
+    fn error(&self, msg: String) -> Error {
+        Error::Data {
+            path: self.path.to_string(),
+            msg,
+            span: self.span.clone(),
+        }
+    }
+
+    fn todo_error(&self) -> Error {
+        self.error(format!("field `{}` is still todo", self.path))
+    }
+
+    fn unexpected(&self) -> Unexpected<'de> {
+        match self.value {
+            CbmlValue::String(s) => Unexpected::Str(s),
+            CbmlValue::Number(n) => Unexpected::Float(*n),
+            CbmlValue::Boolean(b) => Unexpected::Bool(*b),
+            CbmlValue::None => Unexpected::Option,
+            CbmlValue::Todo => Unexpected::Other("todo"),
+            CbmlValue::Array(_) => Unexpected::Seq,
+            CbmlValue::Struct(_) | CbmlValue::Map(_) => Unexpected::Map,
+            CbmlValue::EnumField(..) => Unexpected::NewtypeVariant,
+            CbmlValue::EnumUnit(_) => Unexpected::UnitVariant,
+        }
+    }
+
+    /// number 是 f64, 转换为整数时需要是没有小数部分的数字.
+    fn integer(&self) -> Result<Option<f64>, Error> {
+        match self.value {
+            CbmlValue::Number(n) if n.fract() == 0.0 => Ok(Some(*n)),
+            CbmlValue::Todo => Err(self.todo_error()),
+            _ => Ok(None),
+        }
+    }
+}
+
+macro_rules! deserialize_signed {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                match self.integer()? {
+                    Some(n) if n >= i64::MIN as f64 && n < i64::MAX as f64 => {
+                        visitor.visit_i64(n as i64)
+                    }
+                    _ => Err(de::Error::invalid_type(self.unexpected(), &visitor)),
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! deserialize_unsigned {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                match self.integer()? {
+                    Some(n) if n >= 0.0 && n < u64::MAX as f64 => visitor.visit_u64(n as u64),
+                    Some(n) if n < 0.0 => {
+                        Err(de::Error::invalid_value(Unexpected::Signed(n as i64), &visitor))
+                    }
+                    _ => Err(de::Error::invalid_type(self.unexpected(), &visitor)),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            CbmlValue::String(s) => visitor.visit_borrowed_str(s),
+            CbmlValue::Number(n) => {
+                if n.fract() == 0.0 && *n >= i64::MIN as f64 && *n < i64::MAX as f64 {
+                    visitor.visit_i64(*n as i64)
+                } else {
+                    visitor.visit_f64(*n)
+                }
+            }
+            CbmlValue::Boolean(b) => visitor.visit_bool(*b),
+            CbmlValue::None => visitor.visit_none(),
+            CbmlValue::Todo => Err(self.todo_error()),
+            CbmlValue::Array(values) => visitor.visit_seq(SeqDeserializer {
+                iter: values.iter().enumerate(),
+                parent: self,
+            }),
+            CbmlValue::Struct(fields) => visitor.visit_map(MapDeserializer {
+                iter: Box::new(fields.iter()),
+                value: None,
+                parent: self,
+            }),
+            CbmlValue::Map(pairs) => visitor.visit_map(MapDeserializer {
+                iter: Box::new(pairs.iter().map(|(k, v)| (k, v))),
+                value: None,
+                parent: self,
+            }),
+            CbmlValue::EnumField(..) | CbmlValue::EnumUnit(_) => visitor.visit_enum(self),
+        }
+    }
+
+    deserialize_signed! { deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 }
+    deserialize_unsigned! { deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            CbmlValue::Number(n) => visitor.visit_f64(*n),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            CbmlValue::None => visitor.visit_none(),
+            CbmlValue::Todo => Err(self.todo_error()),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            CbmlValue::None => visitor.visit_unit(),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        if name == spanned::NAME && fields == spanned::FIELDS {
+            return visitor.visit_map(SpannedDeserializer {
+                span: self.span.clone().unwrap_or(Span::empty()),
+                value: Some(self),
+                state: 0,
+            });
+        }
+
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.value {
+            CbmlValue::EnumField(..) | CbmlValue::EnumUnit(_) => visitor.visit_enum(self),
+            CbmlValue::Todo => Err(self.todo_error()),
+            _ => Err(de::Error::invalid_type(self.unexpected(), &"enum")),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool char str string bytes byte_buf seq tuple tuple_struct map identifier
+        ignored_any
+    }
+}
+
+/// 数组中的元素, 路径是 `a[0]`.
+struct SeqDeserializer<'de, I>
+where
+    I: Iterator<Item = (usize, &'de CbmlValue)>,
+{
+    iter: I,
+    parent: Deserializer<'de>,
+}
+
+impl<'de, I> SeqAccess<'de> for SeqDeserializer<'de, I>
+where
+    I: Iterator<Item = (usize, &'de CbmlValue)>,
+{
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        let Some((i, value)) = self.iter.next() else {
+            return Ok(None);
+        };
+
+        let de = self.parent.child(value, self.parent.path.index(i));
+        let (path, span) = (de.path.clone(), de.span.clone());
+
+        return seed
+            .deserialize(de)
+            .map(Some)
+            .map_err(|e| e.or_path(&path, &span));
+    }
+}
+
+/// 结构体和 map 中的字段, 路径是 `a.b`.
+struct MapDeserializer<'de> {
+    iter: Box<dyn Iterator<Item = (&'de String, &'de CbmlValue)> + 'de>,
+
+    /// next_key_seed 取出来的值, 等待 next_value_seed 使用.
+    value: Option<(&'de String, &'de CbmlValue)>,
+    parent: Deserializer<'de>,
+}
+
+impl<'de> MapAccess<'de> for MapDeserializer<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        let Some((key, value)) = self.iter.next() else {
+            return Ok(None);
+        };
+
+        self.value = Some((key, value));
+
+        let de = self.parent.child(value, self.parent.path.child(key));
+        return seed
+            .deserialize(BorrowedStrDeserializer::new(key))
+            .map(Some)
+            .map_err(|e: Error| e.or_path(&de.path, &de.span));
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let Some((key, value)) = self.value.take() else {
+            return Err(de::Error::custom(
+                "next_value_seed 需要在 next_key_seed 之后调用",
+            ));
+        };
+
+        let de = self.parent.child(value, self.parent.path.child(key));
+        let (path, span) = (de.path.clone(), de.span.clone());
+
+        return seed.deserialize(de).map_err(|e| e.or_path(&path, &span));
+    }
+}
+
+/// 给 Spanned 使用的 map: { start = (line, column, index), end = ..., value = ... }.
+struct SpannedDeserializer<'de> {
+    span: Span,
+    value: Option<Deserializer<'de>>,
+
+    /// 0: start, 1: end, 2: value.
+    state: usize,
+}
+
+impl<'de> SpannedDeserializer<'de> {
+    fn position(x: &Position) -> impl de::Deserializer<'de, Error = Error> {
+        let x = vec![x.line as u64, x.column as u64, x.character_index as u64];
+        return de::value::SeqDeserializer::new(x.into_iter());
+    }
+}
+
+impl<'de> MapAccess<'de> for SpannedDeserializer<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        let key = match self.state {
+            0 => spanned::START,
+            1 => spanned::END,
+            2 => spanned::VALUE,
+            _ => return Ok(None),
+        };
+
+        return seed
+            .deserialize(BorrowedStrDeserializer::new(key))
+            .map(Some);
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.state += 1;
+
+        match self.state {
+            1 => seed.deserialize(Self::position(&self.span.start)),
+            2 => seed.deserialize(Self::position(&self.span.end)),
+            _ => match self.value.take() {
+                Some(de) => seed.deserialize(de),
+                None => Err(de::Error::custom("Spanned 的值只能读取一次")),
+            },
+        }
+    }
+}
+
+/// `git(url)` 和 `fast`.
+impl<'de> EnumAccess<'de> for Deserializer<'de> {
+    type Error = Error;
+    type Variant = VariantDeserializer<'de>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let (name, value) = match self.value {
+            CbmlValue::EnumField(name, value) => (name, Some(value.as_ref())),
+            CbmlValue::EnumUnit(name) => (name, None),
+            _ => return Err(de::Error::invalid_type(self.unexpected(), &"enum")),
+        };
+
+        let variant = seed
+            .deserialize(name.as_str().into_deserializer())
+            .map_err(|e: Error| e.or_path(&self.path, &self.span))?;
+
+        let de = VariantDeserializer {
+            value,
+            path: self.path.variant(name),
+            parent: self,
+        };
+
+        return Ok((variant, de));
+    }
+}
+
+pub struct VariantDeserializer<'de> {
+    /// 没有值的成员是 None.
+    value: Option<&'de CbmlValue>,
+
+    /// value 的路径, `a.git`.
+    path: ScopeID,
+
+    /// enum 的值 `git(url)` 的 Deserializer.
+    parent: Deserializer<'de>,
+}
+
+impl<'de> VariantDeserializer<'de> {
+    fn payload(self, expected: &str) -> Result<Deserializer<'de>, Error> {
+        let Some(value) = self.value else {
+            return Err(de::Error::invalid_type(Unexpected::UnitVariant, &expected));
+        };
+
+        return Ok(self.parent.child(value, self.path));
+    }
+}
+
+impl<'de> VariantAccess<'de> for VariantDeserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        match self.value {
+            None | Some(CbmlValue::None) => Ok(()),
+            Some(_) => Err(de::Error::invalid_type(
+                Unexpected::NewtypeVariant,
+                &"unit variant",
+            )),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        let de = self.payload("newtype variant")?;
+        let (path, span) = (de.path.clone(), de.span.clone());
+        return seed.deserialize(de).map_err(|e| e.or_path(&path, &span));
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let de = self.payload("tuple variant")?;
+        let (path, span) = (de.path.clone(), de.span.clone());
+        return de::Deserializer::deserialize_seq(de, visitor).map_err(|e| e.or_path(&path, &span));
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let de = self.payload("struct variant")?;
+        let (path, span) = (de.path.clone(), de.span.clone());
+        return de::Deserializer::deserialize_map(de, visitor).map_err(|e| e.or_path(&path, &span));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cbml_project::def_cbml_file::DefCbmlFile;
+    use serde::Deserialize;
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Source {
+        Git { url: String, branch: Option<String> },
+        Path(String),
+        Registry,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Dep {
+        version: String,
+        source: Source,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Config {
+        name: String,
+        port: u16,
+        ratio: f64,
+        debug: bool,
+        tags: Vec<String>,
+        owner: Option<String>,
+        deps: HashMap<String, Dep>,
+    }
+
+    #[test]
+    fn test_from_str() {
+        let code = r##"
+name = "cbml"
+port = 8080
+ratio = 0.5
+debug = false
+tags = ["a", "b"]
+owner = none
+deps = {
+    serde = { version = "1.0", source = registry }
+    local = { version = "0.1", source = path("../local") }
+    remote = { version = "2", source = git({ url = "https://example.com/a.git", branch = none }) }
+}
+"##;
+        let c: Config = from_str(code).unwrap();
+        assert_eq!(c.name, "cbml");
+        assert_eq!(c.port, 8080);
+        assert_eq!(c.ratio, 0.5);
+        assert!(!c.debug);
+        assert_eq!(c.tags, vec!["a", "b"]);
+        assert_eq!(c.owner, None);
+        assert_eq!(c.deps["serde"].source, Source::Registry);
+        assert_eq!(c.deps["local"].source, Source::Path("../local".into()));
+        assert_eq!(
+            c.deps["remote"].source,
+            Source::Git {
+                url: "https://example.com/a.git".into(),
+                branch: None
+            }
+        );
+    }
+
+    #[test]
+    fn test_keyword_field_names() {
+        let code = "type = 1\nint = 2\nstruct = 3\ntodo = 4\nname = 5";
+        let m: HashMap<String, f64> = from_str(code).unwrap();
+        assert_eq!(m["type"], 1.0);
+        assert_eq!(m["int"], 2.0);
+        assert_eq!(m["struct"], 3.0);
+        assert_eq!(m["todo"], 4.0);
+        assert_eq!(m["name"], 5.0);
+    }
+
+    #[test]
+    fn test_error_path() {
+        let code = r##"
+name = "cbml"
+port = 80.5
+ratio = 1
+debug = true
+tags = []
+owner = none
+deps = {}
+"##;
+        let e = from_str::<Config>(code).unwrap_err();
+        let Error::Cbml(errors) = &e else {
+            panic!("{:?}", e);
+        };
+        assert_eq!(errors[0].error_code, 35);
+        assert!(errors[0].msg.ends_with("at `port`"));
+
+        // 错误指向代码中的值.
+        let span = &errors[0].span;
+        assert_eq!(span.start.line, 2);
+        assert!(code[span.start.character_index..].starts_with("80.5"));
+
+        // from_value 时没有位置.
+        let value = CbmlFile::new_from(String::new(), code)
+            .try_to_cbml_value()
+            .unwrap();
+        let e = from_value::<Config>(&value).unwrap_err();
+        let Error::Data { path, span, .. } = &e else {
+            panic!("{:?}", e);
+        };
+        assert_eq!(path, "port");
+        assert_eq!(span, &None);
+
+        let code = r##"
+name = "cbml"
+port = 80
+ratio = 1
+debug = true
+tags = ["a", 1]
+deps = {}
+"##;
+        let e = from_str::<Config>(code).unwrap_err();
+        assert!(e.to_string().ends_with("at `tags[1]`"), "{}", e);
+
+        let code = r##"
+name = "cbml"
+port = 80
+ratio = 1
+debug = true
+tags = []
+deps = { x = { version = "1", source = path(1) } }
+"##;
+        let e = from_str::<Config>(code).unwrap_err();
+        assert!(e.to_string().ends_with("at `deps.x.source.path`"), "{}", e);
+
+        // 缺少字段时路径是所在的结构体.
+        let code = r##"
+name = "cbml"
+port = 80
+ratio = 1
+debug = true
+tags = []
+deps = { x = { source = registry } }
+"##;
+        let e = from_str::<Config>(code).unwrap_err();
+        assert_eq!(e.to_string(), "missing field `version` at `deps.x`");
+
+        let e = from_str::<Config>("name = todo").unwrap_err();
+        let Error::Cbml(errors) = &e else {
+            panic!("{:?}", e);
+        };
+        assert_eq!(errors[0].msg, "field `name` is still todo");
+    }
+
+    #[test]
+    fn test_spanned() {
+        use crate::cbml_serde::spanned::Spanned;
+
+        #[derive(Debug, Deserialize)]
+        struct Spans {
+            port: Spanned<u16>,
+            source: Spanned<Source>,
+            tags: Vec<Spanned<String>>,
+        }
+
+        let code = r##"
+port = 8080
+source = path("../local")
+tags = ["a", "bb"]
+"##;
+        let s: Spans = from_str(code).unwrap();
+        assert_eq!(*s.port.get_ref(), 8080);
+        assert_eq!(s.port.span().start.line, 1);
+
+        let at = |x: &Span| &code[x.start.character_index..];
+        assert!(at(s.port.span()).starts_with("8080\n"));
+        assert!(at(s.source.span()).starts_with("path(\"../local\")\n"));
+        assert_eq!(s.source.into_inner(), Source::Path("../local".into()));
+        assert!(at(s.tags[1].span()).starts_with("\"bb\"]"));
+
+        // 缺少字段时指向所在的结构体.
+        let code = r##"
+deps = {
+    x = { version = "1", source = git({ branch = none }) }
+}
+"##;
+        let e = from_str::<HashMap<String, HashMap<String, Dep>>>(code).unwrap_err();
+        let Error::Cbml(errors) = &e else {
+            panic!("{:?}", e);
+        };
+        assert_eq!(errors[0].msg, "missing field `url` at `deps.x.source.git`");
+        assert!(code[errors[0].span.start.character_index..].starts_with("{ branch"));
+    }
+
+    #[test]
+    fn test_from_typed_file() {
+        // 有类型定义时, 省略了的字段使用默认值.
+        let def_code = r##"
+name: string
+port: number default 80
+ratio: number default 1
+debug: bool default false
+tags: [string] default []
+owner: ?string
+deps: { [string]: { version: string, source: enum { registry
+    path(string)
+    git({ url: string, branch: ?string }) } } } default {}
+"##;
+        let def_file = DefCbmlFile::new_from("test.def.cbml".into(), def_code);
+        assert!(def_file.errors.is_empty(), "{:?}", def_file.errors);
+
+        let f = CbmlFile::new_from_with_typedef("test.cbml".into(), "name = \"a\"", def_file);
+        assert!(f.errors.is_empty(), "{:?}", f.errors);
+
+        let c: Config = f.deserialize().unwrap();
+        assert_eq!(c.port, 80);
+        assert_eq!(c.owner, None);
+        assert!(c.deps.is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+use crate::lexer::token::Span;
+use crate::parser::parser_error::CbmlError;
+use std::fmt;
+
+/// from_str 和 from_value 返回的错误.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// .cbml 代码中的错误, 例如语法错误, 类型错误, 还没有填写的 todo.
+    /// from_str 时无法转换为 rust 的类型的错误也会转换为 CbmlError, 这样可以指出代码中的位置.
+    Cbml(Vec<CbmlError>),
+
+    /// 转换为 rust 的类型时出现的错误.
+    /// path: 出错的字段的路径, 例如 `deps.serde.version`, 最外层出错时是空字符串.
+    /// span: 出错的值在代码中的位置, from_value 和 to_value 时是 None.
+    Data {
+        path: String,
+        msg: String,
+        span: Option<Span>,
+    },
+}
+
+impl Error {
+    pub fn new(path: String, msg: String) -> Self {
+        Self::Data {
+            path,
+            msg,
+            span: None,
+        }
+    }
+
+    /// 还没有记录路径时使用 path 和 span.
+    /// 错误会从最里层的字段开始往外传, 所以最先记录的就是出错的字段.
+    pub(crate) fn or_path(self, path: &dyn fmt::Display, span: &Option<Span>) -> Self {
+        match self {
+            Error::Data { path: p, msg, .. } if p.is_empty() => Error::Data {
+                path: path.to_string(),
+                msg,
+                span: span.clone(),
+            },
+            x => x,
+        }
+    }
+
+    /// 序列化时错误也是从里往外传, 每一层在路径的前面加上自己的字段名或者 `[index]`.
+    pub(crate) fn prepend_path(self, segment: &str) -> Self {
+        match self {
+            Error::Data { path, msg, span } => {
+                let path = if path.is_empty() {
+                    segment.to_string()
+                } else if path.starts_with('[') {
+                    format!("{}{}", segment, path)
+                } else {
+                    format!("{}.{}", segment, path)
+                };
+                Error::Data { path, msg, span }
+            }
+            x => x,
+        }
+    }
+
+    /// 知道位置的错误转换为 CbmlError, 用 CbmlError::report_error 输出.
+    pub(crate) fn with_file_path(self, file_path: &str) -> Self {
+        match self {
+            Error::Data {
+                path,
+                msg,
+                span: Some(span),
+            } => Error::Cbml(vec![CbmlError::err_deserialize(
+                file_path.to_string(),
+                span,
+                &path,
+                &msg,
+            )]),
+            x => x,
+        }
+    }
+
+    /// 输出错误, 有位置的错误会显示 `file:line:col` 和出错的代码.
+    pub fn report_error(&self, source_code: &str) {
+        match self {
+            Error::Cbml(errors) => errors.iter().for_each(|x| x.report_error(source_code)),
+            Error::Data { .. } => println!("error: {}", self),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Cbml(errors) => {
+                let msgs: Vec<&str> = errors.iter().map(|x| x.msg.as_str()).collect();
+                write!(f, "{}", msgs.join("\n"))
+            }
+            Error::Data { path, msg, .. } if path.is_empty() => write!(f, "{}", msg),
+            Error::Data { path, msg, .. } => write!(f, "{} at `{}`", msg, path),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl serde::de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self::new(String::new(), msg.to_string())
+    }
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self::new(String::new(), msg.to_string())
+    }
+}
@@ -0,0 +1,10 @@
+//! 使用 serde 在 .cbml 和 rust 的类型之间转换.
+pub mod de;
+pub mod error;
+pub mod ser;
+pub mod spanned;
+
+pub use de::{from_str, from_value};
+pub use error::Error;
+pub use ser::{to_string, to_string_with_indent, to_value};
+pub use spanned::Spanned;
@@ -0,0 +1,838 @@
+use super::error::Error;
+use crate::cbml_data::cbml_value::{CbmlValue, is_identifier};
+use crate::lexer::token::TokenKind;
+use serde::Serialize;
+use serde::ser::{self, Impossible};
+
+/// 将 T 转换为 .cbml 代码, 使用 4 个空格缩进.
+/// T 需要是结构体或者 map, 每一个字段是 .cbml 中的一行赋值语句.
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Config {
+///     name: String,
+///     port: u16,
+/// }
+///
+/// let code = cbml::to_string(&Config { name: "cbml".into(), port: 8080 }).unwrap();
+/// assert_eq!(code, "name = \"cbml\"\nport = 8080\n");
+/// ```
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    return to_string_with_indent(value, "    ");
+}
+
+/// 跟 to_string 相同, 但是使用 indent 作为每一层的缩进, 例如 `"  "` 或者 `"\t"`.
+pub fn to_string_with_indent<T: Serialize + ?Sized>(
+    value: &T,
+    indent: &str,
+) -> Result<String, Error> {
+    let fields = match to_value(value)? {
+        CbmlValue::Map(pairs) => pairs,
+        CbmlValue::Struct(hash_map) => hash_map.into_iter().collect(),
+        _ => {
+            return Err(ser::Error::custom(
+                "only structs and maps can be written as a .cbml file",
+            ));
+        }
+    };
+
+    let mut re = String::new();
+    for (name, val) in fields {
+        check_field_name(&name)?;
+        re.push_str(&format!(
+            "{} = {}\n",
+            name,
+            val.to_cbml_with_indent(0, indent)
+        ));
+    }
+
+    return Ok(re);
+}
+
+/// 将 T 转换为 CbmlValue.
+/// 结构体会转换为 CbmlValue::Struct, map 会转换为 CbmlValue::Map, 都按照 serialize 的顺序保存.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<CbmlValue, Error> {
+    return value.serialize(Serializer);
+}
+
+/// 结构体的字段名和 .cbml 文件的顶级字段需要是 cbml 的标识符, 关键字在字段名的位置上也可以使用.
+/// map 的 key 可以是任意的字符串, 不是标识符时会输出为字符串.
+fn check_field_name(name: &str) -> Result<(), Error> {
+    if !is_identifier(name) {
+        return Err(ser::Error::custom(format!(
+            "`{}` can not be used as a field name in cbml",
+            name
+        )));
+    }
+
+    return Ok(());
+}
+
+/// cbml 的 number 是 f64, 超过 f64 精度的整数不能写成 number, 例如 2^53 + 1.
+fn int_to_number(v: i128) -> Result<CbmlValue, Error> {
+    let n = v as f64;
+
+    // 2^127 转换为 i128 时会变成 i128::MAX, 所以需要单独判断.
+    if n >= 2f64.powi(127) || n as i128 != v {
+        return Err(ser::Error::custom("integer out of range for cbml number"));
+    }
+
+    return Ok(CbmlValue::Number(n));
+}
+
+/// 跟 int_to_number 相同.
+fn uint_to_number(v: u128) -> Result<CbmlValue, Error> {
+    let n = v as f64;
+
+    if n >= 2f64.powi(128) || n as u128 != v {
+        return Err(ser::Error::custom("integer out of range for cbml number"));
+    }
+
+    return Ok(CbmlValue::Number(n));
+}
+
+/// enum 的成员名字在值的位置上使用, 不能是关键字, 例如 `source = none` 中的 none 不是成员.
+fn check_variant_name(name: &str) -> Result<(), Error> {
+    check_field_name(name)?;
+
+    let is_keyword = !matches!(
+        TokenKind::Identifier(name.to_string()).handle_keyword(),
+        TokenKind::Identifier(_)
+    );
+
+    if is_keyword {
+        return Err(ser::Error::custom(format!(
+            "`{}` is a keyword and can not be used as a variant name in cbml",
+            name
+        )));
+    }
+
+    return Ok(());
+}
+
+/// 生成 CbmlValue 的 serde::Serializer.
+pub struct Serializer;
+
+impl ser::Serializer for Serializer {
+    type Ok = CbmlValue;
+    type Error = Error;
+
+    type SerializeSeq = SerializeVec;
+    type SerializeTuple = SerializeVec;
+    type SerializeTupleStruct = SerializeVec;
+    type SerializeTupleVariant = SerializeVec;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeMap;
+
+    fn serialize_bool(self, v: bool) -> Result<CbmlValue, Error> {
+        Ok(CbmlValue::Boolean(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<CbmlValue, Error> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<CbmlValue, Error> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<CbmlValue, Error> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<CbmlValue, Error> {
+        int_to_number(v as i128)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<CbmlValue, Error> {
+        int_to_number(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<CbmlValue, Error> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<CbmlValue, Error> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<CbmlValue, Error> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<CbmlValue, Error> {
+        uint_to_number(v as u128)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<CbmlValue, Error> {
+        uint_to_number(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<CbmlValue, Error> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<CbmlValue, Error> {
+        // NaN 和 inf 无法用 cbml 表示.
+        if !v.is_finite() {
+            return Err(ser::Error::custom(format!(
+                "`{}` can not be written as a cbml number",
+                v
+            )));
+        }
+
+        Ok(CbmlValue::Number(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<CbmlValue, Error> {
+        Ok(CbmlValue::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<CbmlValue, Error> {
+        Ok(CbmlValue::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<CbmlValue, Error> {
+        Ok(CbmlValue::Array(
+            v.iter().map(|x| CbmlValue::Number(*x as f64)).collect(),
+        ))
+    }
+
+    fn serialize_none(self) -> Result<CbmlValue, Error> {
+        Ok(CbmlValue::None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<CbmlValue, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<CbmlValue, Error> {
+        Ok(CbmlValue::None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<CbmlValue, Error> {
+        Ok(CbmlValue::None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<CbmlValue, Error> {
+        check_variant_name(variant)?;
+        Ok(CbmlValue::EnumUnit(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<CbmlValue, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<CbmlValue, Error> {
+        check_variant_name(variant)?;
+        Ok(CbmlValue::EnumField(
+            variant.to_string(),
+            Box::new(value.serialize(self)?),
+        ))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeVec, Error> {
+        Ok(SerializeVec {
+            variant: None,
+            values: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeVec, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeVec, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVec, Error> {
+        check_variant_name(variant)?;
+        Ok(SerializeVec {
+            variant: Some(variant),
+            values: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap, Error> {
+        Ok(SerializeMap {
+            variant: None,
+            is_struct: false,
+            pairs: Vec::with_capacity(len.unwrap_or(0)),
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeMap, Error> {
+        Ok(SerializeMap {
+            variant: None,
+            is_struct: true,
+            pairs: Vec::with_capacity(len),
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeMap, Error> {
+        check_variant_name(variant)?;
+        Ok(SerializeMap {
+            variant: Some(variant),
+            is_struct: true,
+            pairs: Vec::with_capacity(len),
+            next_key: None,
+        })
+    }
+}
+
+/// 数组和 tuple, variant 不是 None 时是 `name([...])`.
+pub struct SerializeVec {
+    variant: Option<&'static str>,
+    values: Vec<CbmlValue>,
+}
+
+impl SerializeVec {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let index = format!("[{}]", self.values.len());
+        let value = value
+            .serialize(Serializer)
+            .map_err(|e| e.prepend_path(&index))?;
+        self.values.push(value);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<CbmlValue, Error> {
+        let array = CbmlValue::Array(self.values);
+        match self.variant {
+            Some(name) => Ok(CbmlValue::EnumField(name.to_string(), Box::new(array))),
+            None => Ok(array),
+        }
+    }
+}
+
+impl ser::SerializeSeq for SerializeVec {
+    type Ok = CbmlValue;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<CbmlValue, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SerializeVec {
+    type Ok = CbmlValue;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<CbmlValue, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeVec {
+    type Ok = CbmlValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<CbmlValue, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeVec {
+    type Ok = CbmlValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<CbmlValue, Error> {
+        self.finish()
+    }
+}
+
+/// 结构体和 map, 按照 serialize 的顺序保存字段.
+/// variant 不是 None 时是 `name({...})`.
+pub struct SerializeMap {
+    variant: Option<&'static str>,
+
+    /// 结构体转换为 CbmlValue::Struct, map 转换为 CbmlValue::Map.
+    is_struct: bool,
+    pairs: Vec<(String, CbmlValue)>,
+
+    /// serialize_key 之后等待 serialize_value 的 key.
+    next_key: Option<String>,
+}
+
+impl SerializeMap {
+    fn insert<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> Result<(), Error> {
+        let value = value
+            .serialize(Serializer)
+            .map_err(|e| e.prepend_path(&key))?;
+        self.pairs.push((key, value));
+        Ok(())
+    }
+
+    fn finish(self) -> Result<CbmlValue, Error> {
+        let map = if self.is_struct {
+            CbmlValue::Struct(self.pairs.into_iter().collect())
+        } else {
+            CbmlValue::Map(self.pairs)
+        };
+        match self.variant {
+            Some(name) => Ok(CbmlValue::EnumField(name.to_string(), Box::new(map))),
+            None => Ok(map),
+        }
+    }
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = CbmlValue;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        let key = key.serialize(MapKeySerializer)?;
+        self.next_key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let Some(key) = self.next_key.take() else {
+            return Err(ser::Error::custom(
+                "serialize_value 需要在 serialize_key 之后调用",
+            ));
+        };
+
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<CbmlValue, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = CbmlValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        check_field_name(key)?;
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<CbmlValue, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeMap {
+    type Ok = CbmlValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        check_field_name(key)?;
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<CbmlValue, Error> {
+        self.finish()
+    }
+}
+
+/// map 的 key 只能是 string, 跟 `{ [string]: T }` 相同.
+struct MapKeySerializer;
+
+impl MapKeySerializer {
+    fn key_must_be_string<T>() -> Result<T, Error> {
+        Err(ser::Error::custom("map key must be a string"))
+    }
+}
+
+impl ser::Serializer for MapKeySerializer {
+    type Ok = String;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<String, Error>;
+    type SerializeTuple = Impossible<String, Error>;
+    type SerializeTupleStruct = Impossible<String, Error>;
+    type SerializeTupleVariant = Impossible<String, Error>;
+    type SerializeMap = Impossible<String, Error>;
+    type SerializeStruct = Impossible<String, Error>;
+    type SerializeStructVariant = Impossible<String, Error>;
+
+    fn serialize_str(self, v: &str) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_char(self, v: char) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String, Error> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<String, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<String, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<String, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<String, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<String, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<String, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<String, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<String, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<String, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<String, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<String, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_none(self) -> Result<String, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<String, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_unit(self) -> Result<String, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Self::key_must_be_string()
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Self::key_must_be_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cbml_serde::de::from_str;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Source {
+        Git { url: String, branch: Option<String> },
+        Path(String),
+        Registry,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Dep {
+        version: String,
+        source: Source,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Config {
+        name: String,
+        port: u16,
+        tags: Vec<String>,
+        owner: Option<String>,
+        deps: BTreeMap<String, Dep>,
+    }
+
+    fn config() -> Config {
+        let mut deps = BTreeMap::new();
+        deps.insert(
+            "local".to_string(),
+            Dep {
+                version: "0.1".into(),
+                source: Source::Path("../local".into()),
+            },
+        );
+        deps.insert(
+            "remote".to_string(),
+            Dep {
+                version: "2".into(),
+                source: Source::Git {
+                    url: "https://example.com/a.git".into(),
+                    branch: None,
+                },
+            },
+        );
+        deps.insert(
+            "serde".to_string(),
+            Dep {
+                version: "1.0".into(),
+                source: Source::Registry,
+            },
+        );
+
+        Config {
+            name: "cbml".into(),
+            port: 8080,
+            tags: vec!["a".into(), "say \"hi\"".into()],
+            owner: None,
+            deps,
+        }
+    }
+
+    #[test]
+    fn test_to_string() {
+        let code = to_string(&config()).unwrap();
+
+        // 字段的顺序跟结构体定义的顺序相同.
+        let expected = r##"name = "cbml"
+port = 8080
+tags = ["a", """say "hi""""]
+owner = none
+deps = {
+    local = {
+        version = "0.1"
+        source = path("../local")
+    }
+    remote = {
+        version = "2"
+        source = git({
+            url = "https://example.com/a.git"
+            branch = none
+        })
+    }
+    serde = {
+        version = "1.0"
+        source = registry
+    }
+}
+"##;
+        assert_eq!(code, expected);
+        assert_eq!(from_str::<Config>(&code).unwrap(), config());
+
+        let code = to_string_with_indent(&config(), "\t").unwrap();
+        assert!(
+            code.contains("\n\tlocal = {\n\t\tversion = \"0.1\"\n"),
+            "{}",
+            code
+        );
+        assert_eq!(from_str::<Config>(&code).unwrap(), config());
+    }
+
+    #[test]
+    fn test_to_string_errors() {
+        assert!(to_string(&1).is_err());
+
+        let mut m = BTreeMap::new();
+        m.insert("a b", 1);
+        assert!(to_string(&m).is_err());
+
+        // 嵌套的 map 的 key 可以是任意的字符串, 不是标识符的 key 会输出为字符串.
+        let mut keys = BTreeMap::new();
+        for key in ["serde-json", "_JAVA_OPTIONS", "type", "a b", "", "名字"] {
+            keys.insert(key.to_string(), 1.0);
+        }
+        let mut m = BTreeMap::new();
+        m.insert("deps".to_string(), keys);
+        let code = to_string(&m).unwrap();
+        assert!(code.contains("\n    \"serde-json\" = 1\n"), "{}", code);
+        assert!(code.contains("\n    type = 1\n"), "{}", code);
+        assert_eq!(
+            from_str::<BTreeMap<String, BTreeMap<String, f64>>>(&code).unwrap(),
+            m
+        );
+
+        // 关键字可以作为字段名, 但是不能作为 enum 的成员名字.
+        let mut m = BTreeMap::new();
+        m.insert("type", 1.0);
+        let code = to_string(&m).unwrap();
+        assert_eq!(code, "type = 1\n");
+        assert_eq!(
+            from_str::<BTreeMap<String, f64>>(&code).unwrap()["type"],
+            1.0
+        );
+
+        #[derive(Serialize)]
+        enum Kind {
+            #[serde(rename = "none")]
+            Nothing,
+        }
+        let mut m = BTreeMap::new();
+        m.insert("kind", Kind::Nothing);
+        assert!(to_string(&m).is_err());
+
+        // 超过 f64 精度的整数.
+        let out_of_range = "integer out of range for cbml number";
+        assert_eq!(
+            to_value(&(1i64 << 53)).unwrap(),
+            CbmlValue::Number(9007199254740992.0)
+        );
+        assert_eq!(
+            to_value(&i64::MIN).unwrap(),
+            CbmlValue::Number(i64::MIN as f64)
+        );
+        assert_eq!(
+            to_value(&(1u128 << 100)).unwrap(),
+            CbmlValue::Number(2f64.powi(100))
+        );
+        assert_eq!(
+            to_value(&((1i64 << 53) + 1)).unwrap_err().to_string(),
+            out_of_range
+        );
+        assert_eq!(to_value(&i64::MAX).unwrap_err().to_string(), out_of_range);
+        assert_eq!(to_value(&u64::MAX).unwrap_err().to_string(), out_of_range);
+        assert_eq!(to_value(&i128::MAX).unwrap_err().to_string(), out_of_range);
+        assert_eq!(to_value(&u128::MAX).unwrap_err().to_string(), out_of_range);
+
+        let mut m = BTreeMap::new();
+        m.insert("x", vec![1.0, f64::NAN]);
+        let mut outer = BTreeMap::new();
+        outer.insert("a", m);
+        assert_eq!(
+            to_string(&outer).unwrap_err().to_string(),
+            "`NaN` can not be written as a cbml number at `a.x[1]`"
+        );
+    }
+}
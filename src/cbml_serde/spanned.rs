@@ -0,0 +1,108 @@
+use crate::lexer::token::{Position, Span};
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Deserializer 通过这些名字认出 Spanned, 然后把值的位置传给它.
+pub(crate) const NAME: &str = "$__cbml_private_Spanned";
+pub(crate) const START: &str = "$__cbml_private_start";
+pub(crate) const END: &str = "$__cbml_private_end";
+pub(crate) const VALUE: &str = "$__cbml_private_value";
+pub(crate) const FIELDS: &[&str] = &[START, END, VALUE];
+
+/// 带有在代码中的位置的值, 用来在通过了类型检查之后继续报告指向代码的错误.
+/// ```
+/// use cbml::Spanned;
+///
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     port: Spanned<u16>,
+/// }
+///
+/// let code = "port = 80";
+/// let c: Config = cbml::from_str(code).unwrap();
+/// assert_eq!(*c.port.get_ref(), 80);
+/// assert_eq!(c.port.span().start.column, 7);
+/// ```
+/// 使用 from_value 时没有位置, span 是 Span::empty().
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned<T> {
+    span: Span,
+    value: T,
+}
+
+impl<T> Spanned<T> {
+    pub fn new(span: Span, value: T) -> Self {
+        Self { span, value }
+    }
+
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.value
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Spanned<T> {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SpannedVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for SpannedVisitor<T> {
+            type Value = Spanned<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a spanned value")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Spanned<T>, A::Error> {
+                let mut start: Option<Position> = None;
+                let mut end: Option<Position> = None;
+                let mut value: Option<T> = None;
+
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        START => start = Some(position(map.next_value()?)),
+                        END => end = Some(position(map.next_value()?)),
+                        VALUE => value = Some(map.next_value()?),
+                        _ => return Err(de::Error::unknown_field(&key, FIELDS)),
+                    }
+                }
+
+                let (Some(start), Some(end), Some(value)) = (start, end, value) else {
+                    return Err(de::Error::custom("Spanned 只能用于 cbml 的 Deserializer"));
+                };
+
+                return Ok(Spanned::new(Span { start, end }, value));
+            }
+        }
+
+        deserializer.deserialize_struct(NAME, FIELDS, SpannedVisitor(PhantomData))
+    }
+}
+
+/// (line, column, character_index)
+fn position(x: (u32, u32, usize)) -> Position {
+    Position {
+        line: x.0,
+        column: x.1,
+        character_index: x.2,
+    }
+}
+
+/// 序列化时只输出值.
+impl<T: Serialize> Serialize for Spanned<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serializer)
+    }
+}
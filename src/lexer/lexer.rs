@@ -10,6 +10,9 @@ enum State {
     HexNumber,
     InNumber, // 十进制数字.
     InString,
+    /// raw string, 里面存储的是开头的双引号的数量.
+    /// """ say "hello" """
+    InRawString(usize),
     InDocComment,   // 文档注释 ///
     InBlockComment, // 块注释 /* */
     InLineComment,  // 单行注释 //
@@ -89,6 +92,14 @@ impl Lexer {
         self.input.get(self.position).copied()
     }
 
+    /// 从当前位置开始, 连续的双引号的数量.
+    fn count_double_quotes(&self) -> usize {
+        self.input[self.position..]
+            .iter()
+            .take_while(|x| **x == '"')
+            .count()
+    }
+
     pub fn tokenize(&mut self) -> LexerResult {
         use crate::lexer::token::TokenKind as tk;
 
@@ -199,14 +210,55 @@ impl Lexer {
                             let loc = self.get_pos();
                             tokens.push(Token::new(tk::QuestionMark, loc, self.gen_token_id()));
                         }
+                        '.' if self.input.get(self.position + 1) == Some(&'.') => {
+                            // 范围 `1..10` `1..=10`
+                            self.mark_start_pos();
+                            self.advance();
+                            self.advance();
+
+                            let kind = if self.peek() == Some('=') {
+                                self.advance();
+                                tk::DotDotEq
+                            } else {
+                                tk::DotDot
+                            };
+
+                            self.mark_end_pos();
+                            let loc = self.get_pos();
+                            tokens.push(Token::new(kind, loc, self.gen_token_id()));
+                        }
 
                         '"' => {
                             self.current.clear();
 
                             self.mark_start_pos();
-                            self.push_and_advance(ch);
 
-                            self.state = State::InString;
+                            match self.count_double_quotes() {
+                                1 => {
+                                    self.advance(); // eat this character
+                                    self.state = State::InString;
+                                }
+                                2 => {
+                                    // 空字符串 ""
+                                    self.advance();
+                                    self.advance();
+                                    self.mark_end_pos();
+                                    let loc = self.get_pos();
+
+                                    tokens.push(Token::new(
+                                        tk::String(String::new()),
+                                        loc,
+                                        self.gen_token_id(),
+                                    ));
+                                }
+                                quote_count => {
+                                    // 三个或者更多的双引号开头的是 raw string.
+                                    for _ in 0..quote_count {
+                                        self.advance();
+                                    }
+                                    self.state = State::InRawString(quote_count);
+                                }
+                            }
                         }
                         '/' => {
                             self.current.clear();
@@ -399,7 +451,8 @@ impl Lexer {
                         '0'..='9' => {
                             self.push_and_advance(ch);
                         }
-                        '.' => {
+                        // `1..10` 中的 `..` 不是小数点.
+                        '.' if self.input.get(self.position + 1) != Some(&'.') => {
                             match self.current.find(|x| x == '.') {
                                 Some(_) => {
                                     // 已经有小数点了, 不能重复出现小数点.
@@ -469,12 +522,12 @@ impl Lexer {
                         '"' => {
                             // string ends.
 
-                            self.push_and_advance(ch);
+                            self.advance(); // eat this character
                             self.mark_end_pos();
 
                             let loc = self.get_pos();
 
-                            // self.current 存放 String 字面量的内容. 包含 双引号.
+                            // self.current 存放 String 字面量转义之后的内容. 不包含 双引号.
 
                             tokens.push(Token::new(
                                 tk::String(std::mem::take(&mut self.current)),
@@ -487,141 +540,48 @@ impl Lexer {
                         }
                         '\\' => {
                             // 处理转义字符
-                            self.push_and_advance(ch);
-                            let sadf = self.advance();
+                            self.lex_escape(&mut errors);
+                        }
+                        x => {
+                            self.push_and_advance(x);
+                        }
+                    }
+                }
+                State::InRawString(quote_count) => {
+                    match ch {
+                        '"' => {
+                            let found = self.count_double_quotes();
 
-                            if let Some(c) = sadf {
-                                self.current.push(c);
-                            }
+                            if found >= quote_count {
+                                // raw string ends.
+                                // 多出来的双引号属于字符串的内容, 最后的 quote_count 个双引号是结束符.
 
-                            // 字符的转义交由后续来处理, 这里仅仅将 源代码 中的字符串字面量完整记录下来.
-
-                            // if let Some(next_ch) = self.peek() {
-                            //     match next_ch {
-                            //         'n' => {
-                            //             self.push_and_advance('\n');
-                            //         }
-                            //         'r' => {
-                            //             self.push_and_advance('\r');
-                            //         }
-                            //         't' => {
-                            //             self.push_and_advance('\t');
-                            //         }
-                            //         'u' => {
-                            //             // Unicode 转义 "\u{1F600}"
-
-                            //             self.advance(); //
-
-                            //             let mut unicode = String::new();
-                            //             if let Some(next_ch) = self.peek() {
-                            //                 if next_ch == '{' {
-                            //                     self.advance();
-                            //                 } else {
-                            //                     return Err(ParserError {
-                            //                         file_path: self.file_path.clone(),
-                            //                         msg: format!(
-                            //                             "需要一个 {{ , 而不是 {:?}",
-                            //                             next_ch
-                            //                         ),
-                            //                         code_location: self.get_pos(),
-                            //                         note: None,
-                            //                         help: None,
-                            //                     });
-                            //                 }
-                            //             } else {
-                            //                 // return Err(format!(
-                            //                 //     "需要一个 {{ , 而不是 {:?}",
-                            //                 //     next_ch
-                            //                 // ));
-                            //                 return Err(ParserError {
-                            //                     file_path: self.file_path.clone(),
-                            //                     msg: format!("需要一个 {{ , 而不是 {:?}", next_ch),
-                            //                     code_location: self.get_pos(),
-                            //                     note: None,
-                            //                     help: None,
-                            //                 });
-                            //             }
-                            //             // for _ in 0..4 {
-                            //             for _ in 0..10 {
-                            //                 // \u{1F600} // 大括号中的 hex number 字符数量暂时设置为不超过 10 个.
-
-                            //                 if let Some(next_ch) = self.peek() {
-                            //                     if next_ch == '}' {
-                            //                         self.advance();
-                            //                         break;
-                            //                     } else {
-                            //                         unicode.push(next_ch);
-                            //                         self.advance();
-                            //                     }
-                            //                 } else {
-                            //                     return Err(ParserError {
-                            //                         file_path: self.file_path.clone(),
-                            //                         msg: format!(
-                            //                             "需要一个 {{ , 而不是 {:?}",
-                            //                             next_ch
-                            //                         ),
-                            //                         code_location: self.get_pos(),
-                            //                         note: None,
-                            //                         help: None,
-                            //                     });
-                            //                 }
-                            //             }
-
-                            //             if let Ok(unicode) = u32::from_str_radix(&unicode, 16) {
-                            //                 let sadf = match std::char::from_u32(unicode) {
-                            //                     Some(ch) => ch,
-                            //                     None => {
-                            //                         // return Err(format!(
-                            //                         //     "无效的 unicode 转义序列 {:?}",
-                            //                         //     unicode
-                            //                         // ));
-
-                            //                         return Err(ParserError {
-                            //                             file_path: self.file_path.clone(),
-                            //                             msg: format!(
-                            //                                 "无效的 unicode 转义序列 {:?}",
-                            //                                 unicode
-                            //                             ),
-                            //                             code_location: self.get_pos(),
-                            //                             note: None,
-                            //                             help: None,
-                            //                         });
-                            //                     }
-                            //                 };
-
-                            //                 self.push_and_advance(sadf);
-                            //             } else {
-                            //                 return Err(ParserError {
-                            //                     file_path: self.file_path.clone(),
-                            //                     msg: format!(
-                            //                         "无效的 unicode 转义序列 {:?}",
-                            //                         unicode
-                            //                     ),
-                            //                     code_location: self.get_pos(),
-                            //                     note: None,
-                            //                     help: None,
-                            //                 });
-                            //             }
-                            //         }
-                            //         '\\' => {
-                            //             self.push_and_advance('\\');
-                            //             // self.advance();
-                            //         }
-                            //         '"' => {
-                            //             self.push_and_advance('\"');
-                            //             // self.advance();
-                            //         }
-                            //         '0' => {
-                            //             self.push_and_advance('\0');
-                            //             // self.advance();
-                            //         }
-                            //         _ => {
-                            //             self.push_and_advance(ch);
-                            //         }
-                            //     }
-                            // }
+                                for _ in 0..(found - quote_count) {
+                                    self.current.push('"');
+                                }
+                                for _ in 0..found {
+                                    self.advance();
+                                }
+                                self.mark_end_pos();
+
+                                let loc = self.get_pos();
+
+                                tokens.push(Token::new(
+                                    tk::String(std::mem::take(&mut self.current)),
+                                    loc,
+                                    self.gen_token_id(),
+                                ));
+
+                                self.state = State::Initial;
+                                self.current.clear();
+                            } else {
+                                for _ in 0..found {
+                                    self.push_and_advance('"');
+                                }
+                            }
                         }
                         x => {
+                            // raw string 中没有转义字符.
                             self.push_and_advance(x);
                         }
                     }
@@ -886,7 +846,7 @@ impl Lexer {
                         #[cfg(debug_assertions)]
                         todo!();
                     }
-                    State::InString => {
+                    State::InString | State::InRawString(_) => {
                         // string ends.
 
                         self.mark_end_pos();
@@ -943,6 +903,91 @@ impl Lexer {
     }
 }
 
+impl Lexer {
+    /// 处理字符串中的转义字符, 调用时 self.peek() 是 `\\`.
+    /// 转义之后的字符会被添加到 self.current 中.
+    fn lex_escape(&mut self, errors: &mut Vec<CbmlError>) {
+        let escape_start = self.get_current_position();
+        self.advance(); // eat `\`
+
+        let Some(ch) = self.advance() else {
+            // 字符串没有结束就到了文件末尾.
+            return;
+        };
+
+        match ch {
+            'n' => self.current.push('\n'),
+            'r' => self.current.push('\r'),
+            't' => self.current.push('\t'),
+            '\\' => self.current.push('\\'),
+            '"' => self.current.push('"'),
+            '\'' => self.current.push('\''),
+            '0' => self.current.push('\0'),
+            'u' => {
+                // Unicode 转义 "\u{1F600}"
+                let mut hex = String::new();
+                let mut is_closed = false;
+
+                if self.peek() == Some('{') {
+                    self.advance();
+
+                    while let Some(c) = self.peek() {
+                        if c == '}' {
+                            self.advance();
+                            is_closed = true;
+                            break;
+                        }
+
+                        // 大括号中最多 6 个 hex 数字.
+                        if !c.is_ascii_hexdigit() || hex.len() >= 6 {
+                            break;
+                        }
+
+                        hex.push(c);
+                        self.advance();
+                    }
+                }
+
+                let span = Span {
+                    start: escape_start,
+                    end: self.get_current_position(),
+                };
+
+                let code_point = if is_closed {
+                    u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32)
+                } else {
+                    None
+                };
+
+                match code_point {
+                    Some(c) => self.current.push(c),
+                    None => {
+                        let e = CbmlError::err_invalid_unicode_escape(
+                            self.file_path.clone(),
+                            span,
+                            &hex,
+                            is_closed,
+                        );
+                        errors.push(e);
+                    }
+                }
+            }
+            x => {
+                let span = Span {
+                    start: escape_start,
+                    end: self.get_current_position(),
+                };
+
+                let e = CbmlError::err_invalid_escape_sequence(self.file_path.clone(), span, x);
+                errors.push(e);
+
+                // 保留原来的字符, 继续解析.
+                self.current.push(x);
+            }
+        }
+    }
+}
+
 impl Lexer {
     fn get_current_position(&self) -> Position {
         Position::new(self.line as u32, self.column as u32, self.position)
@@ -1009,7 +1054,7 @@ mod test {
         let sdf: Vec<TokenKind> = vec![
             TokenKind::NewLine,
             TokenKind::Use,
-            TokenKind::String("\"./1.typedef.cbml\"".to_string()),
+            TokenKind::String("./1.typedef.cbml".to_string()),
             TokenKind::NewLine,
             TokenKind::LineComment("// line comment \n".to_string()),
             TokenKind::DocComment("/// doc comment \n".to_string()),
@@ -1021,15 +1066,15 @@ mod test {
             TokenKind::NewLine,
             TokenKind::Identifier("name".to_string()),
             TokenKind::Asign,
-            TokenKind::String("\"new\"".to_string()),
+            TokenKind::String("new".to_string()),
             TokenKind::NewLine,
             TokenKind::Identifier("version".to_string()),
             TokenKind::Asign,
-            TokenKind::String("\"0.1.0\"".to_string()),
+            TokenKind::String("0.1.0".to_string()),
             TokenKind::NewLine,
             TokenKind::Identifier("edition".to_string()),
             TokenKind::Asign,
-            TokenKind::String("\"2021\"".to_string()),
+            TokenKind::String("2021".to_string()),
             TokenKind::NewLine,
             TokenKind::RBrace,
             TokenKind::NewLine,
@@ -1041,26 +1086,26 @@ mod test {
             TokenKind::LBrace,
             TokenKind::Identifier("name".to_string()),
             TokenKind::Asign,
-            TokenKind::String("\"chenbao_cmd\"".to_string()),
+            TokenKind::String("chenbao_cmd".to_string()),
             TokenKind::Comma,
             TokenKind::Identifier("git".to_string()),
             TokenKind::Asign,
-            TokenKind::String("\"ssh://git@github.com/chen-bao-x/chenbao_cmd.git\"".to_string()),
+            TokenKind::String("ssh://git@github.com/chen-bao-x/chenbao_cmd.git".to_string()),
             TokenKind::Comma,
             TokenKind::Identifier("branch".to_string()),
             TokenKind::Asign,
-            TokenKind::String("\"master\"".to_string()),
+            TokenKind::String("master".to_string()),
             TokenKind::RBrace,
             TokenKind::Comma,
             TokenKind::NewLine,
             TokenKind::LBrace,
             TokenKind::Identifier("name".to_string()),
             TokenKind::Asign,
-            TokenKind::String("\"colored\"".to_string()),
+            TokenKind::String("colored".to_string()),
             TokenKind::Comma,
             TokenKind::Identifier("varsion".to_string()),
             TokenKind::Asign,
-            TokenKind::String("\"3.0.0\"".to_string()),
+            TokenKind::String("3.0.0".to_string()),
             TokenKind::Comma,
             TokenKind::RBrace,
             TokenKind::NewLine,
@@ -1085,7 +1130,7 @@ mod test {
             TokenKind::NewLine,
             TokenKind::Identifier("str".to_string()),
             TokenKind::Asign,
-            TokenKind::String("\"string\"".to_string()),
+            TokenKind::String("string".to_string()),
             TokenKind::NewLine,
             TokenKind::Identifier("num".to_string()),
             TokenKind::Colon,
@@ -1102,6 +1147,102 @@ mod test {
         assert_eq!(tokens, sdf);
     }
 
+    fn lex_one_string(code: &str) -> (String, Vec<CbmlError>) {
+        let a = Lexer::new("file_path", code).tokenize();
+
+        let Some(TokenKind::String(s)) = a.tokens.first().map(|x| x.kind.clone()) else {
+            panic!("need a string token: {:?}", code);
+        };
+
+        return (s, a.errors);
+    }
+
+    #[test]
+    fn test_string_escape() {
+        let cases = [
+            (r#""a\nb""#, "a\nb"),
+            (r#""a\rb\tc""#, "a\rb\tc"),
+            (r#""C:\\Users\\cbml""#, "C:\\Users\\cbml"),
+            (r#""say \"hi\"""#, "say \"hi\""),
+            (r#""\'""#, "'"),
+            (r#""\0""#, "\0"),
+            (r#""\u{4F60}\u{597D}，\u{4E16}\u{754C}""#, "你好，世界"),
+            (r#""\u{1F600}""#, "😀"),
+            ("\"多行\n字符串\"", "多行\n字符串"),
+        ];
+
+        for (code, expected) in cases {
+            let (s, errors) = lex_one_string(code);
+            assert!(errors.is_empty(), "{:?}", errors);
+            assert_eq!(s, expected);
+        }
+    }
+
+    #[test]
+    fn test_raw_string() {
+        let cases = [
+            // (开头和结尾的双引号数量, 内容)
+            (3, r#"say "hello""#),
+            (3, r#"C:\Users\cbml"#),
+            (4, r#"a """ b"#),
+            (3, r#"ends with ""#),
+            (3, "\nmy name is \"hello\"\n"),
+        ];
+
+        for (quote_count, expected) in cases {
+            let quotes = "\"".repeat(quote_count);
+            let code = format!("{}{}{}", quotes, expected, quotes);
+
+            let (s, errors) = lex_one_string(&code);
+            assert!(errors.is_empty(), "{:?}", errors);
+            assert_eq!(s, expected);
+        }
+
+        // "" 是空字符串, 后面有只有 `""` 的行时也是.
+        for code in ["a = \"\"\nb = \"\"", "a = \"\"\nb = [\n    \"\"\n]\n"] {
+            let a = Lexer::new("file_path", code).tokenize();
+            assert!(a.errors.is_empty(), "{:?}", a.errors);
+            let strings: Vec<TokenKind> = a
+                .tokens
+                .into_iter()
+                .map(|x| x.kind)
+                .filter(|x| x.kind_is(&TokenKind::String(String::new())))
+                .collect();
+            assert_eq!(
+                strings,
+                vec![
+                    TokenKind::String(String::new()),
+                    TokenKind::String(String::new())
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_string_invalid_escape() {
+        let (_, errors) = lex_one_string(r#""abc \q""#);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].error_code, 0020);
+        assert_eq!(errors[0].span.start.column, 5);
+        assert_eq!(errors[0].span.end.column, 7);
+
+        let bad_unicode = [
+            r#""\u{110000}""#, // 超出 unicode 范围.
+            r#""\u{D800}""#,   // surrogate.
+            r#""\u{}""#,
+            r#""\u{1234567}""#,
+            r#""\u0041""#,
+            r#""\u{zz}""#,
+        ];
+
+        for code in bad_unicode {
+            let (_, errors) = lex_one_string(code);
+            assert_eq!(errors.len(), 1, "{}", code);
+            assert_eq!(errors[0].error_code, 0021, "{}", code);
+            assert_eq!(errors[0].span.start.column, 1, "{}", code);
+        }
+    }
+
     const EXMAPLE_1: &str = r###"
     use "./1.typedef.cbml"
 // line comment 
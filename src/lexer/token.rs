@@ -33,7 +33,7 @@ impl std::cmp::PartialEq for Token {
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum TokenKind {
-    /// 里面存储的是未转义的原始内容.
+    /// 里面存储的是转义之后的内容, 不包含开头和结尾的双引号.
     String(String), // staring literal
     Number(f64),    // number literal 十进制 二进制 十六进制 写法.
     LineComment(String),
@@ -55,6 +55,8 @@ pub enum TokenKind {
     Asign,        // =
     NewLine,      // new line
     DoubleQuote,  // "
+    DotDot,       // ..
+    DotDotEq,     // ..=
 
     // key words
     True,    // true
@@ -63,13 +65,15 @@ pub enum TokenKind {
     Any,     // any
     Struct,  // struct
     Union,   // union
-    // Todo,    // todo
+    Todo,    // todo
     Use,     // use
     Default, // default
     Enum,    // enum
+    Type,    // type
 
     StringTy,  // bool
     NumberTy,  // number
+    IntTy,     // int
     BooleanTy, // bool
 
     #[allow(dead_code)]
@@ -86,19 +90,47 @@ impl TokenKind {
                 "false" => TokenKind::False,
                 "none" => TokenKind::TkNone,
                 "any" => TokenKind::Any,
-                // "struct" => TokenKind::Struct,
-                // "union" => TokenKind::Union,
+                "struct" => TokenKind::Struct,
+                "union" => TokenKind::Union,
+                "todo" => TokenKind::Todo,
                 "use" => TokenKind::Use,
                 "default" => TokenKind::Default,
                 "enum" => TokenKind::Enum,
+                "type" => TokenKind::Type,
                 "string" => TokenKind::StringTy,
                 "number" => TokenKind::NumberTy,
+                "int" => TokenKind::IntTy,
                 "bool" => TokenKind::BooleanTy,
                 _ => TokenKind::Identifier(s.clone()),
             },
             _ => self.clone(),
         }
     }
+
+    /// 在字段名的位置上, 关键字也是字段名, 例如 `type = "git"`, `int: number`.
+    /// 关键字只在语句的开头 (`type X = ...`) 和类型的位置 (`int(1..)`) 上是关键字.
+    pub fn field_name(&self) -> Option<String> {
+        match self {
+            TokenKind::Identifier(s) => Some(s.clone()),
+            TokenKind::True
+            | TokenKind::False
+            | TokenKind::TkNone
+            | TokenKind::Any
+            | TokenKind::Struct
+            | TokenKind::Union
+            | TokenKind::Todo
+            | TokenKind::Use
+            | TokenKind::Default
+            | TokenKind::Enum
+            | TokenKind::Type
+            | TokenKind::StringTy
+            | TokenKind::NumberTy
+            | TokenKind::IntTy
+            | TokenKind::BooleanTy => Some(self.to_cbml_code()),
+            _ => None,
+        }
+    }
+
     pub fn kind_is(&self, other: &Self) -> bool {
         match (self, other) {
             (TokenKind::String(_), TokenKind::String(_)) => true,
@@ -126,15 +158,19 @@ impl TokenKind {
             (TokenKind::Any, TokenKind::Any) => true,
             (TokenKind::Struct, TokenKind::Struct) => true,
             (TokenKind::Union, TokenKind::Union) => true,
-            // (TokenKind::Todo, TokenKind::Todo) => true,
+            (TokenKind::Todo, TokenKind::Todo) => true,
             (TokenKind::Use, TokenKind::Use) => true,
             (TokenKind::Default, TokenKind::Default) => true,
             (TokenKind::StringTy, TokenKind::StringTy) => true,
             (TokenKind::NumberTy, TokenKind::NumberTy) => true,
+            (TokenKind::IntTy, TokenKind::IntTy) => true,
             (TokenKind::BooleanTy, TokenKind::BooleanTy) => true,
             (TokenKind::Enum, TokenKind::Enum) => true,
+            (TokenKind::Type, TokenKind::Type) => true,
             (TokenKind::EOF, TokenKind::EOF) => true,
             (TokenKind::DoubleQuote, TokenKind::DoubleQuote) => true,
+            (TokenKind::DotDot, TokenKind::DotDot) => true,
+            (TokenKind::DotDotEq, TokenKind::DotDotEq) => true,
 
             _ => false,
         }
@@ -146,7 +182,7 @@ impl TokenKind {
             TokenKind::BooleanTy => "bool",
             TokenKind::Colon => ":",
             TokenKind::Comma => ",",
-            TokenKind::String(n) => &format!("{}", n),
+            TokenKind::String(n) => &format!("{:?}", n),
             TokenKind::Number(n) => &format!("{}", n),
             TokenKind::Identifier(n) => &format!("{}", n),
             TokenKind::Invalid(n) => &format!("{}", n),
@@ -171,15 +207,19 @@ impl TokenKind {
             // TokenKind::Any => "",
             TokenKind::Struct => "struct",
             TokenKind::Union => "union",
-            // TokenKind::Todo => "todo",
+            TokenKind::Todo => "todo",
             TokenKind::Use => "use",
             TokenKind::Default => "default",
             TokenKind::StringTy => "string",
             TokenKind::NumberTy => "number",
+            TokenKind::IntTy => "int",
 
             TokenKind::Enum => "enum",
+            TokenKind::Type => "type",
             TokenKind::EOF => "",
             TokenKind::DoubleQuote => "\"",
+            TokenKind::DotDot => "..",
+            TokenKind::DotDotEq => "..=",
         };
 
         return format!("{}", a);
@@ -1,11 +1,20 @@
-mod cbml_codable;
-
-// pub use cbml_codable::*;
+// 让 `#[derive(CbmlCodable)]` 生成的 `::cbml::...` 在本 crate 中也能使用.
+extern crate self as cbml;
+
+pub mod cbml_codable;
+pub use cbml_codable::{CbmlCodable, CodableError};
+pub use cbml_derive::CbmlCodable;
+// CbmlValue::Struct 的字段类型.
+pub use indexmap::IndexMap;
 pub mod cbml_data;
 pub use cbml_data::cbml_type::*;
 pub use cbml_data::cbml_value::*;
 pub use cbml_root::*;
 pub mod cbml_project;
+pub mod cbml_serde;
+pub use cbml_serde::{
+    Spanned, from_str, from_value, to_string, to_string_with_indent, to_value,
+};
 pub mod lexer;
 pub mod parser;
 
@@ -84,12 +93,12 @@ mod tests {
             .unwrap()
             .fields_map
             .iter()
-            .for_each(|ref x| println!("name: {}, scope: {}", x.1.name, x.1.scope_id.0));
+            .for_each(|ref x| println!("name: {}, scope: {}", x.1.name, x.1.scope_id));
 
         println!("asdf.fields");
         asdf.fields
             .iter()
-            .for_each(|ref x| println!("name: {}, scope: {}", x.name, x.scope.0));
+            .for_each(|ref x| println!("name: {}, scope: {}", x.name, x.scope));
     }
 
     fn test_typedef(path: &str) {
@@ -147,15 +156,16 @@ where
     }
 }
 
-use std::collections::HashMap;
 pub trait AndThenTo<'a> {
     fn cbml_str(&self) -> Option<&'a str>;
     fn cbml_number(&self) -> Option<f64>;
     fn cbml_bool(&self) -> Option<bool>;
     fn cbml_none(&self) -> Option<CbmlNoneValue>;
     fn cbml_array(&self) -> Option<&'a Vec<CbmlValue>>;
-    fn cbml_struct(&self) -> Option<&'a HashMap<String, CbmlValue>>;
+    fn cbml_struct(&self) -> Option<&'a IndexMap<String, CbmlValue>>;
+    fn cbml_map(&self) -> Option<&'a Vec<(String, CbmlValue)>>;
     fn cbml_enum_field(&self) -> Option<(String, Box<CbmlValue>)>;
+    fn cbml_enum_unit(&self) -> Option<&'a str>;
     fn look_up(&self) -> &Self;
 }
 
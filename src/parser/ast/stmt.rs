@@ -1,6 +1,7 @@
-use std::collections::HashMap;
+use indexmap::IndexMap;
 
-use crate::cbml_data::cbml_value::CbmlValue;
+use crate::cbml_data::cbml_value::{CbmlValue, key_to_cbml, string_to_cbml};
+use crate::cbml_data::cbml_type::{ArrayConstraint, NumberRange, StringConstraint};
 
 use crate::{ToCbml, ToCbmlValue};
 use crate::lexer::token::Span;
@@ -54,7 +55,7 @@ impl StmtKind {
             StmtKind::DocComment(d) => d.span,
             StmtKind::EmptyLine => todo!(),
             StmtKind::TypeDef(type_def_stmt) => match type_def_stmt {
-                // TypeDefStmt::TypeAliasStmt(type_alias_stmt) => type_alias_stmt.name_span,
+                TypeDefStmt::TypeAliasStmt(type_alias_stmt) => type_alias_stmt.name_span,
                 TypeDefStmt::StructDefStmt(struct_def) => struct_def.name_span,
                 TypeDefStmt::EnumDef(enum_def) => enum_def.name_span,
                 TypeDefStmt::UnionDef(union_def) => union_def.name_span,
@@ -68,6 +69,7 @@ pub enum TypeDefStmt {
     StructDefStmt(StructDef),
     EnumDef(EnumDef), // enum Haha { ssh(string), git( {url: string, branch: string} ) }
     UnionDef(UnionDef), // 具名 union
+    TypeAliasStmt(TypeAliasStmt), // type name = type
 }
 
 impl TypeDefStmt {
@@ -76,6 +78,7 @@ impl TypeDefStmt {
             TypeDefStmt::StructDefStmt(struct_def) => struct_def.name_span.clone(),
             TypeDefStmt::EnumDef(enum_def) => enum_def.name_span.clone(),
             TypeDefStmt::UnionDef(union_def) => union_def.name_span.clone(),
+            TypeDefStmt::TypeAliasStmt(type_alias_stmt) => type_alias_stmt.name_span.clone(),
         }
     }
 
@@ -84,29 +87,21 @@ impl TypeDefStmt {
             TypeDefStmt::StructDefStmt(struct_def) => &struct_def.struct_name,
             TypeDefStmt::EnumDef(enum_def) => &enum_def.enum_name,
             TypeDefStmt::UnionDef(union_def) => &union_def.union_name,
+            TypeDefStmt::TypeAliasStmt(type_alias_stmt) => &type_alias_stmt.name,
         }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct UseStmt {
-    /// use "path/to/file"  包含开头和结尾的 双引号, self.url 中保存的是未转译的原始代码.
+    /// use "path/to/file"  不包含开头和结尾的 双引号, self.url 中保存的是转义之后的内容.
     pub url: String,
     pub keyword_span: Span,
     pub url_span: Span,
 }
 impl UseStmt {
     pub fn get_use_url(&self) -> String {
-        // 处理空字符串的情况""
-        if self.url.len() < 2 {
-            return self.url.clone();
-        }
-
-        let len = self.url.len();
-        let start = 1; // 去掉开头的 双引号
-        let end = len - 1; // 去掉结尾的 双引号
-
-        return self.url[start..end].to_string();
+        return self.url.clone();
     }
 }
 
@@ -177,7 +172,8 @@ pub struct DocumentStmt {
 pub struct EnumFieldDef {
     pub field_name: String,
     // pub _type: TypeSignStmtKind,
-    pub _type: TypeSignStmt,
+    /// 没有值的成员是 None, 例如 `fast`.
+    pub _type: Option<TypeSignStmt>,
 
     pub field_name_span: Span,
 }
@@ -208,18 +204,22 @@ pub enum LiteralKind {
     Array(Vec<Literal>),        // [1,2,2]
     Struct(Vec<AsignmentStmt>), // 结构体字面量暂时先不做.
     /// enum field literal
+    /// `git(url)`, 没有值的成员 `fast` 的 literal 是 None.
     EnumFieldLiteral {
         field_name: String,
-        literal: Box<Literal>,
+        literal: Option<Box<Literal>>,
         span: Span,
     },
 
     // Optional,
     LiteralNone, // none
 
-                 // /// 这个可能会留下隐患, 暂时先不支持 todo 功能.
-                 // Todo,
-                 // Default,
+    /// todo
+    /// 还没有填写的值, 类型检查时只是警告, 转换为具体的类型时会报错.
+    Todo,
+    /// default
+    /// 使用 .def.cbml 中字段定义的默认值.
+    Default,
 }
 
 impl ToCbmlValue for LiteralKind {
@@ -232,7 +232,7 @@ impl ToCbmlValue for LiteralKind {
                 CbmlValue::Array(literals.iter().map(|x| x.to_cbml_value()).collect())
             }
             LiteralKind::Struct(asignment_stmts) => {
-                let mut fields: HashMap<String, CbmlValue> = HashMap::new();
+                let mut fields: IndexMap<String, CbmlValue> = IndexMap::new();
 
                 for x in asignment_stmts {
                     fields.insert(x.field_name.clone(), x.value.to_cbml_value());
@@ -241,11 +241,20 @@ impl ToCbmlValue for LiteralKind {
                 return CbmlValue::Struct(fields);
             }
             LiteralKind::LiteralNone => CbmlValue::None,
+            LiteralKind::Todo => CbmlValue::Todo,
+            // 没有字段定义时无法得知默认值,
+            // CbmlFile 转换为 CbmlValue 时会使用字段定义中的默认值.
+            LiteralKind::Default => CbmlValue::None,
             LiteralKind::EnumFieldLiteral {
                 field_name,
                 literal,
                 ..
-            } => CbmlValue::EnumField(field_name, Box::new(literal.to_cbml_value())),
+            } => match literal {
+                Some(literal) => {
+                    CbmlValue::EnumField(field_name, Box::new(literal.to_cbml_value()))
+                }
+                None => CbmlValue::EnumUnit(field_name),
+            },
         }
     }
 }
@@ -265,8 +274,12 @@ pub struct TypeSignStmt {
 // #[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq)]
 pub enum TypeSignStmtKind {
-    String,  // string
-    Number,  // number
+    /// string string(1..=64, email)
+    String { constraints: Vec<StringConstraint> },
+    /// number number(0..1)
+    Number { range: Option<NumberRange> },
+    /// int int(1..=65535)
+    Int { range: Option<NumberRange> },
     Boolean, // bool
     Any,     // any
 
@@ -289,7 +302,11 @@ pub enum AnonymousTypeDefKind {
     /// 匿名数组类型
     /// [Type]
     // Array { inner_type: Box<TypeSignStmtKind> },
-    Array { inner_type: Box<TypeSignStmt> },
+    /// [Type](1..=10, unique)
+    Array {
+        inner_type: Box<TypeSignStmt>,
+        constraints: Vec<ArrayConstraint>,
+    },
 
     /// 匿名 enum
     Enum {
@@ -303,6 +320,19 @@ pub enum AnonymousTypeDefKind {
         alowd_values: Vec<CbmlValue>, // 1 | 2 | 3 | "asdf" | false | [1,2,3]
     }, // 匿名联合类型
 
+    /// 由类型组成的联合类型
+    /// number | string
+    TypeUnion {
+        types: Vec<TypeSignStmt>,
+    },
+
+    /// 匿名 map 类型
+    /// { [string]: number }
+    Map {
+        key_type: Box<TypeSignStmt>,
+        value_type: Box<TypeSignStmt>,
+    },
+
     Optional {
         inner_type: Box<TypeSignStmt>,
         // span: Span,
@@ -375,14 +405,14 @@ pub struct UnionDef {
 }
 
 impl UnionDef {
-    #[allow(dead_code)]
-    pub fn duplicate_check(&self) -> Vec<LiteralKind> {
+    /// 返回重复出现的值, 第一次出现的不算重复.
+    pub fn duplicate_check(&self) -> Vec<&Literal> {
         let mut re: Vec<&LiteralKind> = Vec::new();
-        let mut duplicated: Vec<LiteralKind> = Vec::new();
+        let mut duplicated: Vec<&Literal> = Vec::new();
 
         for v in &self.allowed_values {
             if re.contains(&&v.kind) {
-                duplicated.push(v.kind.clone());
+                duplicated.push(v);
             } else {
                 re.push(&v.kind)
             }
@@ -463,7 +493,7 @@ impl ToCbml for StmtKind {
             StmtKind::DocComment(s) => format!("{}", s.document),
             StmtKind::EmptyLine => "\n".to_string(),
             StmtKind::TypeDef(type_def_stmt) => match type_def_stmt {
-                // TypeDefStmt::TypeAliasStmt(type_alias_stmt) => type_alias_stmt.to_cbml_code(deepth),
+                TypeDefStmt::TypeAliasStmt(type_alias_stmt) => type_alias_stmt.to_cbml(deepth),
                 TypeDefStmt::StructDefStmt(struct_def) => struct_def.to_cbml(deepth),
                 TypeDefStmt::EnumDef(enum_def) => enum_def.to_cbml(deepth),
                 TypeDefStmt::UnionDef(union_def) => union_def.to_cbml(deepth),
@@ -476,17 +506,14 @@ impl ToCbml for UseStmt {
     fn to_cbml(&self, deepth: usize) -> String {
         _ = deepth;
 
-        if self.url.starts_with("\"") && self.url.ends_with("\"") {
-            return format!("use {}", self.url);
-        } else {
-            format!("use \"{}\"", self.url)
-        }
+        format!("use {}", string_to_cbml(&self.url))
     }
 }
 
 impl ToCbml for TypeAliasStmt {
     fn to_cbml(&self, deepth: usize) -> String {
         let mut re = String::new();
+        re.push_str(&doc_to_cbml(&self.doc, deepth));
         re.push_str(&format!("type {} = ", self.name));
         re.push_str(&self.ty.to_cbml(deepth));
         re.push_str("\n");
@@ -501,7 +528,7 @@ impl ToCbml for AsignmentStmt {
         re.push_str(&format!(
             "{}{} = {}",
             "    ".repeat(deepth),
-            self.field_name,
+            key_to_cbml(&self.field_name),
             self.value.kind.to_cbml(deepth)
         ));
 
@@ -521,16 +548,25 @@ impl ToCbml for Vec<StructFieldDefStmt> {
         return re;
     }
 }
+/// 文档注释, 每一行以 `///` 开始.
+fn doc_to_cbml(doc: &Option<DocumentStmt>, deepth: usize) -> String {
+    let mut re = String::new();
+
+    if let Some(doc) = doc {
+        doc.document.lines().for_each(|x| {
+            re.push_str(&"    ".repeat(deepth));
+            re.push_str(&format!("///{}\n", x));
+        });
+    }
+
+    return re;
+}
+
 impl ToCbml for StructFieldDefStmt {
     fn to_cbml(&self, deepth: usize) -> String {
         let mut re = String::new();
 
-        if let Some(doc) = &self.doc {
-            doc.document.lines().for_each(|x| {
-                re.push_str(&"    ".repeat(deepth));
-                re.push_str(&format!("///{}\n", x));
-            });
-        }
+        re.push_str(&doc_to_cbml(&self.doc, deepth));
         re.push_str(&"    ".repeat(deepth));
         re.push_str(&self.field_name);
         re.push_str(": ");
@@ -550,9 +586,11 @@ impl ToCbml for EnumFieldDef {
         let mut re = String::new();
         re.push_str(&"    ".repeat(deepth));
         re.push_str(&self.field_name);
-        re.push_str("(");
-        re.push_str(&self._type.to_cbml(deepth));
-        re.push_str(") ");
+        if let Some(ty) = &self._type {
+            re.push_str("(");
+            re.push_str(&ty.to_cbml(deepth));
+            re.push_str(")");
+        }
 
         return re;
     }
@@ -567,11 +605,7 @@ impl ToCbml for Literal {
 impl ToCbml for LiteralKind {
     fn to_cbml(&self, deepth: usize) -> String {
         match self {
-            LiteralKind::String(s) => {
-                let mut re = String::new();
-                re.push_str(&format!("{}", s));
-                return re;
-            }
+            LiteralKind::String(s) => string_to_cbml(s),
             LiteralKind::Number(n) => {
                 let mut re = String::new();
                 re.push_str(&format!("{}", n));
@@ -671,27 +705,29 @@ impl ToCbml for LiteralKind {
             } => {
                 let mut re = String::new();
                 re.push_str(_field_name);
-                re.push('(');
 
-                re.push_str(&_literal.to_cbml(deepth));
-
-                re.push(')');
+                if let Some(_literal) = _literal {
+                    re.push('(');
+                    re.push_str(&_literal.to_cbml(deepth));
+                    re.push(')');
+                }
                 return re;
             }
             LiteralKind::LiteralNone => {
                 let mut re = String::new();
                 re.push_str("none");
                 return re;
-            } // LiteralKind::Todo => {
-              //     let mut re = String::new();
-              //     re.push_str("todo");
-              //     return re;
-              // }
-              // LiteralKind::Default => {
-              //     let mut re = String::new();
-              //     re.push_str("default");
-              //     return re;
-              // }
+            }
+            LiteralKind::Todo => {
+                let mut re = String::new();
+                re.push_str("todo");
+                return re;
+            }
+            LiteralKind::Default => {
+                let mut re = String::new();
+                re.push_str("default");
+                return re;
+            }
         }
     }
 }
@@ -709,8 +745,22 @@ impl ToCbml for TypeSignStmt {
 impl ToCbml for TypeSignStmtKind {
     fn to_cbml(&self, deepth: usize) -> String {
         match self {
-            TypeSignStmtKind::String => format!("string"),
-            TypeSignStmtKind::Number => format!("number"),
+            TypeSignStmtKind::String { constraints } => {
+                if constraints.is_empty() {
+                    return format!("string");
+                }
+
+                let constraints: Vec<String> = constraints.iter().map(|x| x.to_cbml(0)).collect();
+                format!("string({})", constraints.join(", "))
+            }
+            TypeSignStmtKind::Number { range } => match range {
+                Some(range) => format!("number({})", range),
+                None => format!("number"),
+            },
+            TypeSignStmtKind::Int { range } => match range {
+                Some(range) => format!("int({})", range),
+                None => format!("int"),
+            },
             TypeSignStmtKind::Boolean => format!("bool"),
             TypeSignStmtKind::Any => format!("any"),
 
@@ -722,12 +772,12 @@ impl ToCbml for TypeSignStmtKind {
                         str.push_str(&format!("enum {{\n",));
 
                         for field in fields {
-                            str.push_str(&format!(
-                                "{}{}({})\n",
-                                "    ".repeat(deepth + 1),
-                                field.field_name,
-                                field._type.to_cbml(deepth + 1)
-                            ));
+                            str.push_str(&"    ".repeat(deepth + 1));
+                            str.push_str(&field.field_name);
+                            if let Some(ty) = &field._type {
+                                str.push_str(&format!("({})", ty.to_cbml(deepth + 1)));
+                            }
+                            str.push('\n');
                         }
 
                         str.push_str(&"    ".repeat(deepth));
@@ -764,8 +814,36 @@ impl ToCbml for TypeSignStmtKind {
 
                         return str;
                     }
-                    AnonymousTypeDefKind::Array { inner_type } => {
-                        format!("[{}]", inner_type.to_cbml(deepth + 1))
+                    AnonymousTypeDefKind::TypeUnion { types } => {
+                        let types: Vec<String> =
+                            types.iter().map(|x| x.to_cbml(deepth)).collect();
+                        return types.join(" | ");
+                    }
+                    AnonymousTypeDefKind::Array {
+                        inner_type,
+                        constraints,
+                    } => {
+                        if constraints.is_empty() {
+                            return format!("[{}]", inner_type.to_cbml(deepth + 1));
+                        }
+
+                        let constraints: Vec<String> =
+                            constraints.iter().map(|x| x.to_cbml(0)).collect();
+                        format!(
+                            "[{}]({})",
+                            inner_type.to_cbml(deepth + 1),
+                            constraints.join(", ")
+                        )
+                    }
+                    AnonymousTypeDefKind::Map {
+                        key_type,
+                        value_type,
+                    } => {
+                        format!(
+                            "{{ [{}]: {} }}",
+                            key_type.to_cbml(deepth),
+                            value_type.to_cbml(deepth)
+                        )
                     }
                     AnonymousTypeDefKind::Optional { inner_type } => {
                         format!("?{}", inner_type.to_cbml(deepth))
@@ -780,6 +858,7 @@ impl ToCbml for StructDef {
     fn to_cbml(&self, deepth: usize) -> String {
         let mut re = String::new();
 
+        re.push_str(&doc_to_cbml(&self.doc, deepth));
         re.push_str(&format!("struct {} {{\n", self.struct_name));
 
         re.push_str(&self.fields.to_cbml(deepth + 1));
@@ -794,6 +873,7 @@ impl ToCbml for EnumDef {
     fn to_cbml(&self, deepth: usize) -> String {
         let mut re = String::new();
 
+        re.push_str(&doc_to_cbml(&self.doc, deepth));
         re.push_str(&format!("enum {} ", self.enum_name));
         re.push_str("{\n");
         for field in &self.fields {
@@ -809,17 +889,19 @@ impl ToCbml for EnumDef {
 impl ToCbml for UnionDef {
     fn to_cbml(&self, deepth: usize) -> String {
         let mut re = String::new();
-        re.push_str(&format!("union {} = ", self.union_name));
-
-        re.push_str("(");
+        re.push_str(&doc_to_cbml(&self.doc, deepth));
+        re.push_str("union(");
         re.push_str(&self.base_type.to_cbml(deepth));
-        re.push_str(")");
+        re.push_str(") ");
+        re.push_str(&self.union_name);
         re.push_str(" = ");
 
-        for x in &self.allowed_values {
-            re.push_str(&x.kind.to_cbml(deepth));
-            re.push_str(" | ");
-        }
+        let values: Vec<String> = self
+            .allowed_values
+            .iter()
+            .map(|x| x.kind.to_cbml(deepth))
+            .collect();
+        re.push_str(&values.join(" | "));
 
         re.push_str("\n");
         return re;
@@ -2,7 +2,9 @@ use super::CbmlError;
 use super::ast::stmt::*;
 
 
-use crate::{dp, ToCbmlValue};
+use crate::cbml_data::cbml_type::{ArrayConstraint, NumberRange, StringConstraint, StringPattern};
+use crate::cbml_data::string_format::StringFormat;
+use crate::ToCbmlValue;
 use crate::lexer::token::TokenKind as tk;
 use crate::lexer::token::*;
 
@@ -106,7 +108,8 @@ impl<'a> CbmlParser<'a> {
 
         let tok = self.peek().kind.clone();
         match tok {
-            tk::Identifier(_) => {
+            // `type = 1` 是字段, `type a = string` 是类型别名.
+            _ if matches!(tok, tk::Identifier(_)) || self.is_field_name_start() => {
                 let next_tok = self.peek_next(1);
                 if next_tok.kind.kind_is(&tk::Asign) {
                     // 解析赋值语句
@@ -145,9 +148,10 @@ impl<'a> CbmlParser<'a> {
                 }
             }
             tk::Use => self.parse_use(),
-            // tk::Struct => return self.parse_struct_def(),
-            // tk::Union => self.parse_union_def(),
-            // tk::Enum => self.parse_enum_def(),
+            tk::Struct => return self.parse_struct_def(),
+            tk::Union => self.parse_union_def(),
+            tk::Enum => self.parse_enum_def(),
+            tk::Type => self.parse_type_alias(),
             tk::LineComment(_) => self.parse_line_comment(),
             tk::BlockComment(_) => self.parse_block_comment(),
             tk::DocComment(_) => self.parse_top_field_def(),
@@ -205,8 +209,221 @@ impl<'a> CbmlParser<'a> {
         return Ok(stmt);
     }
 
+    /// number 和 int 后面可选的范围: `(1..=65535)` `(0..)` `(..10)`
+    /// type_span: number 或者 int 的位置.
+    /// return: (范围, 包含范围在内的类型标注的位置)
+    fn parse_number_range(
+        &mut self,
+        type_span: Span,
+    ) -> Result<(Option<NumberRange>, Span), CbmlError> {
+        // number_range = LParen range RParen
+        if !self.check(&tk::LParen) {
+            return Ok((None, type_span));
+        }
+
+        self.consume(tk::LParen)?;
+        let range = self.parse_range()?;
+        let r_tok = self.consume(tk::RParen)?.clone();
+
+        let span = Span {
+            start: type_span.start,
+            end: r_tok.span.end,
+        };
+
+        return Ok((Some(range), span));
+    }
+
+    /// string 后面可选的约束: `(1..=64, hostname)` `("^[a-z]+$")`
+    /// type_span: string 的位置.
+    /// return: (约束, 包含约束在内的类型标注的位置)
+    fn parse_string_constraints(
+        &mut self,
+        type_span: Span,
+    ) -> Result<(Vec<StringConstraint>, Span), CbmlError> {
+        // string_constraints = LParen constraint (Comma constraint){0,} RParen
+        // constraint = range | String | Identifier
+        if !self.check(&tk::LParen) {
+            return Ok((vec![], type_span));
+        }
+
+        self.consume(tk::LParen)?;
+
+        let mut constraints: Vec<StringConstraint> = vec![];
+        loop {
+            let tok = self.peek().clone();
+            let constraint = match tok.kind {
+                tk::String(pattern) => {
+                    self.consume(tk::String("".into()))?;
+
+                    // 正则表达式只在这里编译一次.
+                    let pattern = StringPattern::new(&pattern).map_err(|err| {
+                        CbmlError::err_invalid_regex(self.file_path.clone(), tok.span.clone(), err)
+                    })?;
+                    StringConstraint::Pattern(pattern)
+                }
+                tk::Identifier(name) => {
+                    self.consume(tk::Identifier("".into()))?;
+
+                    let Some(format) = StringFormat::from_name(&name) else {
+                        return Err(CbmlError::err_unknow_string_format(
+                            self.file_path.clone(),
+                            tok.span,
+                            &name,
+                        ));
+                    };
+                    StringConstraint::Format(format)
+                }
+                _ => StringConstraint::Length(self.parse_range()?),
+            };
+            constraints.push(constraint);
+
+            if !self.check(&tk::Comma) {
+                break;
+            }
+            self.consume(tk::Comma)?;
+        }
+
+        let r_tok = self.consume(tk::RParen)?.clone();
+
+        let span = Span {
+            start: type_span.start,
+            end: r_tok.span.end,
+        };
+
+        return Ok((constraints, span));
+    }
+
+    /// 数组后面可选的约束: `(1..=10, unique)`
+    /// type_span: `[T]` 的位置.
+    /// return: (约束, 包含约束在内的类型标注的位置)
+    fn parse_array_constraints(
+        &mut self,
+        type_span: Span,
+    ) -> Result<(Vec<ArrayConstraint>, Span), CbmlError> {
+        // array_constraints = LParen constraint (Comma constraint){0,} RParen
+        // constraint = range | "unique"
+        if !self.check(&tk::LParen) {
+            return Ok((vec![], type_span));
+        }
+
+        self.consume(tk::LParen)?;
+
+        let mut constraints: Vec<ArrayConstraint> = vec![];
+        loop {
+            let tok = self.peek().clone();
+            let constraint = match tok.kind {
+                tk::Identifier(name) => {
+                    self.consume(tk::Identifier("".into()))?;
+
+                    if name != "unique" {
+                        return Err(CbmlError::err_unknow_array_constraint(
+                            self.file_path.clone(),
+                            tok.span,
+                            &name,
+                        ));
+                    }
+                    ArrayConstraint::Unique
+                }
+                _ => ArrayConstraint::Length(self.parse_range()?),
+            };
+            constraints.push(constraint);
+
+            if !self.check(&tk::Comma) {
+                break;
+            }
+            self.consume(tk::Comma)?;
+        }
+
+        let r_tok = self.consume(tk::RParen)?.clone();
+
+        let span = Span {
+            start: type_span.start,
+            end: r_tok.span.end,
+        };
+
+        return Ok((constraints, span));
+    }
+
+    /// `1..10` `1..=10` `1..` `..=10`
+    fn parse_range(&mut self) -> Result<NumberRange, CbmlError> {
+        // range = Number? (DotDot | DotDotEq) Number?
+        let start = match self.peek().kind {
+            tk::Number(n) => {
+                self.consume(tk::Number(0.0))?;
+                Some(n)
+            }
+            _ => None,
+        };
+
+        let inclusive = if self.check(&tk::DotDotEq) {
+            self.consume(tk::DotDotEq)?;
+            true
+        } else {
+            self.consume(tk::DotDot)?;
+            false
+        };
+
+        let end = match self.peek().kind {
+            tk::Number(n) => {
+                self.consume(tk::Number(0.0))?;
+                Some(n)
+            }
+            _ => None,
+        };
+
+        let range = NumberRange {
+            start,
+            end,
+            inclusive,
+        };
+
+        return Ok(range);
+    }
+
     /// 类型标注
     fn parse_type_sign(&mut self) -> Result<TypeSignStmt, CbmlError> {
+        // type_sign = single_type_sign (pipe NewLine{0,} single_type_sign){0,}
+
+        let first = self.parse_single_type_sign()?;
+
+        // 由值组成的 union 已经在 parse_single_type_sign 中把 pipe 都解析完了.
+        if !self.check(&tk::Pipe) {
+            return Ok(first);
+        }
+
+        let start = first.span.start.clone();
+        let mut types: Vec<TypeSignStmt> = vec![first];
+
+        while self.check(&tk::Pipe) {
+            self.consume(tk::Pipe)?; // pipe
+            _ = self.eat_zeor_or_multy(tk::NewLine)?; // NewLine{0,}
+
+            let ty = self.parse_single_type_sign()?;
+            types.push(ty);
+        }
+
+        let span = Span {
+            start,
+            end: types
+                .last()
+                .map(|x| x.span.end.clone())
+                .unwrap_or(self.peek().span.end.clone()),
+        };
+
+        let type_sign = TypeSignStmt {
+            kind: TypeSignStmtKind::Anonymous(AnonymousTypeDefStmt {
+                kind: AnonymousTypeDefKind::TypeUnion { types },
+                node_id: self.gen_node_id(),
+                span: span.clone(),
+            }),
+            span,
+            node_id: self.gen_node_id(),
+        };
+
+        return Ok(type_sign);
+    }
+
+    fn parse_single_type_sign(&mut self) -> Result<TypeSignStmt, CbmlError> {
         // any | string | number | bool | identifier | Anonymous_optinal  | Anonymous_array | Anonymous_struct | Anonymous_union
 
         // 解析类型声明
@@ -224,21 +441,34 @@ impl<'a> CbmlParser<'a> {
                 return Ok(type_sign);
             }
             tk::StringTy => {
-                let _tok = self.consume(tk::StringTy)?;
+                let _tok = self.consume(tk::StringTy)?.clone();
+                let (constraints, span) = self.parse_string_constraints(_tok.span)?;
 
                 let type_sign = TypeSignStmt {
-                    kind: TypeSignStmtKind::String,
-                    span: _tok.span.clone(),
+                    kind: TypeSignStmtKind::String { constraints },
+                    span,
                     node_id: self.gen_node_id(),
                 };
                 return Ok(type_sign);
             }
             tk::NumberTy => {
-                let numberty_tok = self.consume(tk::NumberTy)?;
+                let numberty_tok = self.consume(tk::NumberTy)?.clone();
+                let (range, span) = self.parse_number_range(numberty_tok.span)?;
 
                 let type_sign = TypeSignStmt {
-                    kind: TypeSignStmtKind::Number,
-                    span: numberty_tok.span.clone(),
+                    kind: TypeSignStmtKind::Number { range },
+                    span,
+                    node_id: self.gen_node_id(),
+                };
+                return Ok(type_sign);
+            }
+            tk::IntTy => {
+                let intty_tok = self.consume(tk::IntTy)?.clone();
+                let (range, span) = self.parse_number_range(intty_tok.span)?;
+
+                let type_sign = TypeSignStmt {
+                    kind: TypeSignStmtKind::Int { range },
+                    span,
                     node_id: self.gen_node_id(),
                 };
                 return Ok(type_sign);
@@ -267,7 +497,8 @@ impl<'a> CbmlParser<'a> {
                 // 可选类型
 
                 let question_mark_tok = self.consume(tk::QuestionMark)?.clone();
-                let inner_type = self.parse_type_sign()?;
+                // `?number | string` 是 `(?number) | string`.
+                let inner_type = self.parse_single_type_sign()?;
                 let end_span = inner_type.span.end.clone();
 
                 let type_sign = TypeSignStmt {
@@ -300,22 +531,21 @@ impl<'a> CbmlParser<'a> {
                 _ = self.eat_zeor_or_multy(tk::NewLine);
 
                 let r_tok = self.consume(tk::RBracket)?.clone();
+                let (constraints, span) = self.parse_array_constraints(Span {
+                    start: l_tok.span.start,
+                    end: r_tok.span.end,
+                })?;
 
                 let type_sign = TypeSignStmt {
                     kind: TypeSignStmtKind::Anonymous(super::ast::stmt::AnonymousTypeDefStmt {
                         kind: AnonymousTypeDefKind::Array {
                             inner_type: Box::new(inner_type),
+                            constraints,
                         },
                         node_id: self.gen_node_id(),
-                        span: Span {
-                            start: l_tok.span.start.clone(),
-                            end: r_tok.span.end.clone(),
-                        },
+                        span: span.clone(),
                     }),
-                    span: Span {
-                        start: l_tok.span.start,
-                        end: r_tok.span.end,
-                    },
+                    span,
                     node_id: self.gen_node_id(),
                 };
                 return Ok(type_sign);
@@ -326,6 +556,37 @@ impl<'a> CbmlParser<'a> {
                 // 结构体类型
                 let l_tok = self.consume(tk::LBrace)?.clone();
 
+                _ = self.eat_zeor_or_multy(tk::NewLine)?;
+                if self.check(&tk::LBracket) {
+                    // 匿名 map: { [string]: number }
+                    self.consume(tk::LBracket)?;
+                    let key_type = self.parse_type_sign()?;
+                    self.consume(tk::RBracket)?;
+                    self.consume(tk::Colon)?;
+                    let value_type = self.parse_type_sign()?;
+                    _ = self.eat_zeor_or_multy(tk::NewLine)?;
+                    let r_tok = self.consume(tk::RBrace)?.clone();
+
+                    let span = Span {
+                        start: l_tok.span.start,
+                        end: r_tok.span.end,
+                    };
+
+                    let type_sign = TypeSignStmt {
+                        kind: TypeSignStmtKind::Anonymous(AnonymousTypeDefStmt {
+                            kind: AnonymousTypeDefKind::Map {
+                                key_type: Box::new(key_type),
+                                value_type: Box::new(value_type),
+                            },
+                            node_id: self.gen_node_id(),
+                            span: span.clone(),
+                        }),
+                        span,
+                        node_id: self.gen_node_id(),
+                    };
+                    return Ok(type_sign);
+                }
+
                 let mut fields: Vec<StructFieldDefStmt> = vec![];
                 let mut count = 0;
 
@@ -608,22 +869,33 @@ impl<'a> CbmlParser<'a> {
                     break;
                 }
 
-                tk::Identifier(_) => {
-                    // 解析结构体字段
+                tk::String(key) => {
+                    // map 的 key 可以是任意的字符串: "serde-json" = "1.0"
+
+                    let key_tok = self.consume(tk::String("".into()))?.clone();
+                    self.consume(tk::Asign)?;
 
-                    let name_tok = self.consume(tk::Identifier("".into()))?.clone();
+                    let value = self.parse_literal()?;
 
-                    if let tk::Identifier(name) = name_tok.kind.clone() {
-                        self.consume(tk::Asign)?;
+                    fields.push(AsignmentStmt {
+                        field_name: key,
+                        value,
+                        field_name_span: key_tok.span,
+                    });
+                }
+                x if x.field_name().is_some() => {
+                    // 解析结构体字段
 
-                        let value = self.parse_literal()?;
+                    let (name, name_span) = self.consume_field_name()?;
+                    self.consume(tk::Asign)?;
 
-                        fields.push(AsignmentStmt {
-                            field_name: name.clone(),
-                            value,
-                            field_name_span: name_tok.span,
-                        });
-                    }
+                    let value = self.parse_literal()?;
+
+                    fields.push(AsignmentStmt {
+                        field_name: name,
+                        value,
+                        field_name_span: name_span,
+                    });
                 }
                 x => {
                     let sf = self.consume(x)?.clone();
@@ -649,9 +921,7 @@ impl<'a> CbmlParser<'a> {
         let tok = self.peek().clone();
         match tok.kind {
             tk::String(s) => {
-                let str = s.trim_start_matches("\"").trim_end_matches("\"");
-
-                let a = LiteralKind::String(str.to_string());
+                let a = LiteralKind::String(s);
                 self.consume(tk::String("".into()))?;
 
                 return Ok(Literal {
@@ -694,16 +964,23 @@ impl<'a> CbmlParser<'a> {
                 });
             }
 
-            tk::Default => {
-                let asdf = self.file_path.clone();
-                let default_tok = self.consume(tk::Default)?;
+            tk::Todo => {
+                self.consume(tk::Todo)?;
+
+                return Ok(Literal {
+                    kind: LiteralKind::Todo,
+                    span: tok.span,
+                });
+            }
 
-                let e = CbmlError::err_default_keyword_not_allowed_in_literal(
-                    asdf,
-                    default_tok.span.clone(),
-                );
+            tk::Default => {
+                self.consume(tk::Default)?;
 
-                return Err(e);
+                // default 是否能在这里使用由 CbmlFile 和 DefCbmlFile 检查.
+                return Ok(Literal {
+                    kind: LiteralKind::Default,
+                    span: tok.span,
+                });
             }
             tk::LBracket => {
                 // 数组字面量
@@ -727,19 +1004,25 @@ impl<'a> CbmlParser<'a> {
                     tk::LParen => {
                         // 解析 enum literal
 
-                        let enum_literal = self.parse_enum_literal()?;
+                        return self.parse_enum_literal();
+                    }
+                    _ => {
+                        // 没有值的 enum 成员: fast
+
+                        let name_tok = self.consume(tk::Identifier("".into()))?.clone();
+                        let tk::Identifier(name) = name_tok.kind else {
+                            panic!("这是逻辑上不可能出现的错误.")
+                        };
 
                         return Ok(Literal {
-                            kind: enum_literal,
-                            span: self.peek().span.clone(),
+                            kind: LiteralKind::EnumFieldLiteral {
+                                field_name: name,
+                                literal: None,
+                                span: name_tok.span.clone(),
+                            },
+                            span: name_tok.span,
                         });
                     }
-                    _ => {
-                        return Err(CbmlError::err_unknow_token(
-                            self.file_path.clone(),
-                            next_tok,
-                        ));
-                    }
                 }
             }
             _ => {
@@ -753,42 +1036,26 @@ impl<'a> CbmlParser<'a> {
         // identifier asignment literal
         // 解析赋值语句
 
-        let name_tok = self.consume(tk::Identifier("".into()))?.clone(); // identifier
-
-        if let tk::Identifier(name) = name_tok.kind {
-            self.consume(tk::Asign)?; // asignment
+        let (name, name_span) = self.consume_field_name()?; // identifier
 
-            let value = self.parse_literal()?; // literal
+        self.consume(tk::Asign)?; // asignment
 
-            let stmt = Stmt {
-                span: Span {
-                    start: name_tok.span.start.clone(),
-                    end: value.span.end.clone(),
-                },
-                kind: StmtKind::Asignment(AsignmentStmt {
-                    field_name: name,
-                    value,
-                    field_name_span: name_tok.span.clone(),
-                }),
-                node_id: self.gen_node_id(),
-            };
-
-            return Ok(stmt);
-        } else {
-            dp(format!(
-                "parse_asignment error: unkonow token {:?}",
-                self.peek().clone()
-            ));
+        let value = self.parse_literal()?; // literal
 
-            return Err(CbmlError::new(
-                self.file_path.clone(),
-                format!(
-                    "parse_asignment error: unkonow token {:?}",
-                    self.peek().kind.to_cbml_code()
-                ),
-                self.peek().span.clone(),
-            ));
+        let stmt = Stmt {
+            span: Span {
+                start: name_span.start.clone(),
+                end: value.span.end.clone(),
+            },
+            kind: StmtKind::Asignment(AsignmentStmt {
+                field_name: name,
+                value,
+                field_name_span: name_span,
+            }),
+            node_id: self.gen_node_id(),
         };
+
+        return Ok(stmt);
     }
 
     fn parse_default_value(&mut self) -> Result<Option<Literal>, CbmlError> {
@@ -809,8 +1076,31 @@ impl<'a> CbmlParser<'a> {
     }
 
     fn parse_top_field_def(&mut self) -> Result<Stmt, CbmlError> {
+        let mark_pos = self.current_position;
         let doc = self.parse_document()?;
 
+        // 文档注释后面是类型定义, 类型定义会自己解析文档注释.
+        match self.peek().kind {
+            _ if self.is_field_name_start() => {}
+            tk::Struct => {
+                self.current_position = mark_pos;
+                return self.parse_struct_def();
+            }
+            tk::Enum => {
+                self.current_position = mark_pos;
+                return self.parse_enum_def();
+            }
+            tk::Union => {
+                self.current_position = mark_pos;
+                return self.parse_union_def();
+            }
+            tk::Type => {
+                self.current_position = mark_pos;
+                return self.parse_type_alias();
+            }
+            _ => {}
+        };
+
         // 目前只支持给 定义的字段添加文档.
         let mut field_def = match self.parse_struct_field_def() {
             Ok(v) => v,
@@ -856,17 +1146,7 @@ impl<'a> CbmlParser<'a> {
         };
 
         // 解析字段定义
-        let name_tok = self.consume(tk::Identifier("".into()))?.clone();
-        let tk::Identifier(name) = name_tok.kind.clone() else {
-            return Err(CbmlError::new(
-                self.file_path.clone(),
-                format!(
-                    "parse_field_def error: unkonow token {:?}",
-                    self.peek().kind.to_cbml_code()
-                ),
-                self.peek().span.clone(),
-            ));
-        };
+        let (name, name_span) = self.consume_field_name()?;
 
         _ = self.consume(tk::Colon)?;
 
@@ -875,7 +1155,7 @@ impl<'a> CbmlParser<'a> {
             node_id: self.gen_node_id(),
             kind: field_type.kind,
             span: Span {
-                start: name_tok.span.start.clone(),
+                start: name_span.start.clone(),
                 end: field_type.span.end,
             },
         };
@@ -887,7 +1167,7 @@ impl<'a> CbmlParser<'a> {
             field_name: name,
             _type: type_sign,
             default: default_value,
-            field_name_span: name_tok.span,
+            field_name_span: name_span,
             doc: doc,
             node_id: self.gen_node_id(),
         });
@@ -985,9 +1265,6 @@ impl<'a> CbmlParser<'a> {
     }
 
     /// 解析使用 struct name { } 这种方式定义的结构体.
-
-    #[cfg(debug_assertions)]
-    #[allow(dead_code)]
     fn parse_struct_def(&mut self) -> Result<Stmt, CbmlError> {
         // 解析结构体定义
 
@@ -1142,8 +1419,6 @@ impl<'a> CbmlParser<'a> {
         }
     }
 
-    #[cfg(debug_assertions)]
-    #[allow(dead_code)]
     fn parse_enum_def(&mut self) -> Result<Stmt, CbmlError> {
         // enum identifier LBrace newline{0,} enum_field{0,} RBrace
         // enum_field = newline{0,} identifier LParent typedef RParent newline
@@ -1180,6 +1455,8 @@ impl<'a> CbmlParser<'a> {
                     let asdf = self.parse_enum_field()?;
                     fields.push(asdf);
 
+                    self.eat_zeor_or_multy(tk::NewLine)?;
+
                     // let field_name_tok = self.consume(tk::Identifier("".into())).unwrap();
                     // if let tk::Identifier(field_name) = field_name_tok.kind.clone() {
                     //     self.consume(tk::LParen)?;
@@ -1226,10 +1503,9 @@ impl<'a> CbmlParser<'a> {
         }
     }
 
-    #[cfg(debug_assertions)]
-    #[allow(dead_code)]
     fn parse_union_def(&mut self) -> Result<Stmt, CbmlError> {
-        // union LParent typesign RParent identifier Assignment union_field{1,}
+        // union LParent typesign RParent identifier union_body
+        // union_body = Assignment union_field{1,} | LBrace union_field{1,} RBrace
         // union_field = pipe{1} literal
 
         let doc = match self.parse_document() {
@@ -1275,9 +1551,22 @@ impl<'a> CbmlParser<'a> {
             ));
         };
 
-        self.consume(tk::Asign)?; // Assignment
+        // union(string) name = | "a" | "b"
+        // union(string) name { "a" | "b" }
+        let alowd_values = if let tk::LBrace = self.peek().kind {
+            self.consume(tk::LBrace)?; // LBrace
+            _ = self.eat_zeor_or_multy(tk::NewLine)?; // NewLine{0,}
+            let alowd_values = self.parse_union_fields()?; // union_field{1,}
+            _ = self.eat_zeor_or_multy(tk::NewLine)?; // NewLine{0,}
+            self.consume(tk::RBrace)?; // RBrace
+
+            alowd_values
+        } else {
+            self.consume(tk::Asign)?; // Assignment
+            _ = self.eat_zeor_or_multy(tk::NewLine)?; // NewLine{0,}
 
-        let alowd_values = self.parse_union_fields()?; // union_field{1,}
+            self.parse_union_fields()? // union_field{1,}
+        };
 
         let stmt = Stmt {
             span: Span {
@@ -1297,21 +1586,65 @@ impl<'a> CbmlParser<'a> {
         return Ok(stmt);
     }
 
+    /// type name = type_sign
+    fn parse_type_alias(&mut self) -> Result<Stmt, CbmlError> {
+        // type_alias = document{0,1} type identifier Asign NewLine{0,} type_sign
+
+        let doc = match self.parse_document() {
+            Ok(d) => Some(d),
+            Err(_) => None,
+        };
+
+        let key_word_type = self.consume(tk::Type)?.clone(); // type
+
+        let name_tok = self.consume(tk::Identifier("".into()))?.clone(); // identifier
+        let tk::Identifier(name) = name_tok.kind.clone() else {
+            panic!("这是逻辑上不可能出现的错误.")
+        };
+
+        self.consume(tk::Asign)?; // Asign
+        _ = self.eat_zeor_or_multy(tk::NewLine); // NewLine{0,}
+
+        let ty = self.parse_type_sign()?; // type_sign
+
+        let stmt = Stmt {
+            span: Span {
+                start: key_word_type.span.start,
+                end: ty.span.end.clone(),
+            },
+            kind: StmtKind::TypeAliasStmt(TypeAliasStmt {
+                name,
+                ty,
+                doc,
+                name_span: name_tok.span,
+            }),
+            node_id: self.gen_node_id(),
+        };
+
+        return Ok(stmt);
+    }
+
     fn parse_enum_field(&mut self) -> Result<EnumFieldDef, CbmlError> {
-        // enum_field =   identifier LParent typedef RParent newline
+        // enum_field =   identifier (LParent typedef RParent){0,1} newline
 
         let field_name_tok = self.consume(tk::Identifier("".into()))?.clone();
 
         if let tk::Identifier(field_name) = field_name_tok.kind.clone() {
-            _ = self.eat_zeor_or_multy(tk::NewLine);
-            self.consume(tk::LParen)?; // LParent
-            _ = self.eat_zeor_or_multy(tk::NewLine);
+            // 没有 `(` 的是没有值的成员: fast
+            let mut ty = None;
+            if self.check(&tk::LParen) {
+                self.consume(tk::LParen)?; // LParent
+                _ = self.eat_zeor_or_multy(tk::NewLine);
 
-            let ty = self.parse_type_sign()?; // typedef
-            _ = self.eat_zeor_or_multy(tk::NewLine);
+                ty = Some(self.parse_type_sign()?); // typedef
+                _ = self.eat_zeor_or_multy(tk::NewLine);
+
+                self.consume(tk::RParen)?;
+            }
 
-            self.consume(tk::RParen)?;
-            self.consume(tk::NewLine)?; // ends.
+            if !self.check(&tk::RBrace) {
+                self.consume(tk::NewLine)?; // ends.
+            }
 
             let field = EnumFieldDef {
                 field_name,
@@ -1327,7 +1660,7 @@ impl<'a> CbmlParser<'a> {
         }
     }
 
-    fn parse_enum_literal(&mut self) -> Result<LiteralKind, CbmlError> {
+    fn parse_enum_literal(&mut self) -> Result<Literal, CbmlError> {
         // enum_literal = identifier LParent literal RParent
 
         let name_tok = self.consume(tk::Identifier("".into()))?.clone();
@@ -1338,15 +1671,19 @@ impl<'a> CbmlParser<'a> {
 
             let lit = self.parse_literal()?; // literal
 
-            let _ = self.consume(tk::RParen)?.clone(); // RParent
+            let r_tok = self.consume(tk::RParen)?.clone(); // RParent
 
-            return Ok(LiteralKind::EnumFieldLiteral {
-                field_name: name,
-                literal: lit.into(),
-                span: name_tok.span, // span: Span {
-                                     //     start: l_tok.span.start,
-                                     //     end: r_tok.span.end,
-                                     // },
+            return Ok(Literal {
+                kind: LiteralKind::EnumFieldLiteral {
+                    field_name: name,
+                    literal: Some(lit.into()),
+                    span: name_tok.span.clone(),
+                },
+                // `git(url)` 整个 literal 的位置, 成员名字的位置是上面的 span.
+                span: Span {
+                    start: name_tok.span.start,
+                    end: r_tok.span.end,
+                },
             });
         } else {
             panic!("这是逻辑上不可能出现的错误.");
@@ -1405,6 +1742,30 @@ impl<'a> CbmlParser<'a> {
         }
     }
 
+    /// 当前 Token 是字段名: 标识符或者关键字, 后面是 `=` 或者 `:`.
+    fn is_field_name_start(&self) -> bool {
+        return self.peek().kind.field_name().is_some()
+            && matches!(self.peek_next(1).kind, tk::Asign | tk::Colon);
+    }
+
+    /// 字段名, 关键字在这里也是字段名.
+    fn consume_field_name(&mut self) -> Result<(String, Span), CbmlError> {
+        let tok = self.peek().clone();
+        let Some(name) = tok.kind.field_name() else {
+            return Err(CbmlError::new(
+                self.file_path.clone(),
+                format!(
+                    "Expected field name, but found: {:?}",
+                    tok.kind.to_cbml_code()
+                ),
+                tok.span,
+            ));
+        };
+
+        self.current_position += 1;
+        return Ok((name, tok.span));
+    }
+
     /// 检查当前 Token 是否与期望的 Token 匹配
     fn check(&self, kind: &tk) -> bool {
         if self.is_at_end() {
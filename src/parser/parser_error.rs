@@ -1,9 +1,7 @@
 use std::default;
 
-use crate::{
-    cbml_project::types::FieldDef,
-    lexer::token::{Span, Token},
-};
+use crate::cbml_data::string_format::StringFormat;
+use crate::lexer::token::{Span, Token};
 
 use super::{
     StmtKind,
@@ -164,31 +162,40 @@ impl CbmlError {
     }
 
     /// 0003
-    pub fn err_unknow_field(file_path: String, span: Span, field_name: &str) -> Self {
+    /// path: 没有定义的字段的完整路径, 例如 `p.stret`.
+    /// candidates: 当前 scope 中定义了的字段, 用来提示跟 field_name 相似的字段名.
+    pub fn err_unknow_field(
+        file_path: String,
+        span: Span,
+        path: &str,
+        field_name: &str,
+        candidates: &[&str],
+    ) -> Self {
         Self {
             error_code: (0003),
             file_path,
-            msg: format!("unknow field `{}` ", field_name),
+            msg: format!("unknow field `{}` ", path),
             span,
             note: None,
-            help: None,
+            help: did_you_mean(field_name, candidates),
         }
     }
 
     /// 0004
+    /// path: 类型不对的值的完整路径, 例如 `tree.obj.obj.age`.
     pub fn err_mismatched_types(
         file_path: String,
         span: Span,
+        path: &str,
         expected: &str,
-
         found: &str,
     ) -> Self {
         Self {
             error_code: (0004),
             file_path,
             msg: format!(
-                "mismatched types, expected `{}` found  `{}` ",
-                expected, found
+                "mismatched types at `{}`, expected `{}` found `{}`",
+                path, expected, found
             ),
             span,
             note: None,
@@ -323,8 +330,8 @@ impl CbmlError {
         let adf = match &literal.kind {
             super::ast::stmt::LiteralKind::EnumFieldLiteral { .. } => "enum",
             super::ast::stmt::LiteralKind::LiteralNone => "none",
-            // super::ast::stmt::LiteralKind::Todo => "todo",
-            // super::ast::stmt::LiteralKind::Default => "defaul",
+            super::ast::stmt::LiteralKind::Todo => "todo",
+            super::ast::stmt::LiteralKind::Default => "default",
             _ => "",
         };
 
@@ -347,10 +354,10 @@ impl CbmlError {
     ) -> Self {
         CbmlError {
             file_path,
-            msg: format!("default 关键字只能用于字段申明时使用."),
+            msg: format!("default 关键字不能在这里使用."),
             span: default_tok_span,
             note: Some(format!(
-                "关于 default 关键字的用法, 可查看: [github.com](github.com)"
+                "default 只能直接作为字段的值使用: `name = default`, 这个字段的定义中需要有默认值."
             )),
             help: None,
             error_code: (0013),
@@ -370,22 +377,20 @@ impl CbmlError {
     }
 
     /// 0015
+    /// unasigned_fields: 未赋值字段的完整路径, 例如 `a.b[0].c`.
     pub fn err_has_fields_unasigned(
         file_path: String,
-        unasigned_fields: Vec<&FieldDef>,
+        unasigned_fields: &[String],
         span: Span,
     ) -> Self {
-        let mut sdaf = String::new();
-
-        for x in &unasigned_fields {
-            sdaf.push_str(&x.name);
-            sdaf.push_str(", ");
-        }
-
         Self {
             error_code: (0015),
             file_path,
-            msg: format!("还有 {} 个字段未赋值: {}", unasigned_fields.len(), sdaf),
+            msg: format!(
+                "还有 {} 个字段未赋值: {}",
+                unasigned_fields.len(),
+                unasigned_fields.join(", ")
+            ),
             span: span,
             note: None,
             help: None,
@@ -447,4 +452,327 @@ impl CbmlError {
             error_code: 0019,
         }
     }
+
+    /// 0020
+    pub fn err_invalid_escape_sequence(file_path: String, span: Span, escape_char: char) -> Self {
+        Self {
+            error_code: 0020,
+            file_path,
+            msg: format!("unknown character escape `\\{}`", escape_char),
+            span,
+            note: None,
+            help: Some(format!(
+                "可以使用的转义符: \\n \\r \\t \\u{{HEX}} \\\\ \\\" \\' \\0"
+            )),
+        }
+    }
+
+    /// 0021
+    pub fn err_invalid_unicode_escape(
+        file_path: String,
+        span: Span,
+        hex: &str,
+        is_closed: bool,
+    ) -> Self {
+        let msg = if !is_closed {
+            format!("invalid unicode escape, need `\\u{{HEX}}`")
+        } else if hex.is_empty() {
+            format!("empty unicode escape `\\u{{}}`")
+        } else {
+            format!("invalid unicode code point `\\u{{{}}}`", hex)
+        };
+
+        Self {
+            error_code: 0021,
+            file_path,
+            msg,
+            span,
+            note: Some(format!("unicode 转义的大括号中需要 1 到 6 个 hex 数字.")),
+            help: Some(format!("示例: \"\\u{{4F60}}\\u{{597D}}\" -> \"你好\"")),
+        }
+    }
+
+    /// 0022
+    pub fn err_recursive_type(file_path: String, span: Span, type_name: &str) -> Self {
+        Self {
+            error_code: 0022,
+            file_path,
+            msg: format!("recursive type `{}` has infinite size", type_name),
+            span,
+            note: Some(format!("类型 `{}` 直接或间接的引用了它自己.", type_name)),
+            help: None,
+        }
+    }
+
+    /// 0023
+    pub fn err_type_alias_cycle(file_path: String, span: Span, cycle: &[&str]) -> Self {
+        Self {
+            error_code: 0023,
+            file_path,
+            msg: format!("cycle detected when expanding type alias `{}`", cycle[0]),
+            span,
+            note: Some(format!("{}", cycle.join(" -> "))),
+            help: Some(format!("type alias 不能直接或间接的引用它自己.")),
+        }
+    }
+
+    /// 0024
+    pub fn err_field_is_todo(file_path: String, span: Span, field_path: &str) -> Self {
+        Self {
+            error_code: 0024,
+            file_path,
+            msg: format!("field `{}` is still todo", field_path),
+            span,
+            note: Some(format!(
+                "todo 只是一个占位符, 使用这个配置之前需要填写具体的值."
+            )),
+            help: None,
+        }
+    }
+
+    /// 0025
+    /// candidates: enum 中定义了的 field, 用来提示相似的名字.
+    pub fn err_unknow_enum_field(
+        file_path: String,
+        span: Span,
+        field_name: &str,
+        candidates: &[&str],
+    ) -> Self {
+        Self {
+            error_code: 0025,
+            file_path,
+            msg: format!("unknow enum field `{}`", field_name),
+            span,
+            note: None,
+            help: did_you_mean(field_name, candidates),
+        }
+    }
+
+    /// 0026
+    pub fn err_map_key_must_be_string(file_path: String, span: Span, found: &str) -> Self {
+        Self {
+            error_code: 0026,
+            file_path,
+            msg: format!("map 的 key 只能是 string, found `{}`", found),
+            span,
+            note: None,
+            help: Some(format!("例如: {{ [string]: number }}")),
+        }
+    }
+
+    /// 0027
+    /// path: 超出范围的值的完整路径.
+    pub fn err_number_out_of_range(
+        file_path: String,
+        span: Span,
+        path: &str,
+        range: &str,
+        found: &str,
+    ) -> Self {
+        Self {
+            error_code: 0027,
+            file_path,
+            msg: format!(
+                "value of `{}` is out of range, expected `{}` found `{}`",
+                path, range, found
+            ),
+            span,
+            note: None,
+            help: Some(format!("允许的范围是 `{}`", range)),
+        }
+    }
+
+    /// 0028
+    pub fn err_empty_number_range(file_path: String, span: Span, range: &str) -> Self {
+        Self {
+            error_code: 0028,
+            file_path,
+            msg: format!("range `{}` is empty", range),
+            span,
+            note: None,
+            help: None,
+        }
+    }
+
+    /// 0029
+    pub fn err_unknow_string_format(file_path: String, span: Span, name: &str) -> Self {
+        let formats: Vec<&str> = StringFormat::ALL.iter().map(|x| x.name()).collect();
+
+        Self {
+            error_code: 0029,
+            file_path,
+            msg: format!("unknow string format `{}`", name),
+            span,
+            note: Some(format!("可以使用的格式: {}", formats.join(", "))),
+            help: did_you_mean(name, &formats),
+        }
+    }
+
+    /// 0030
+    pub fn err_invalid_regex(file_path: String, span: Span, err: regex::Error) -> Self {
+        Self {
+            error_code: 0030,
+            file_path,
+            msg: format!("invalid regex: {}", err),
+            span,
+            note: None,
+            help: None,
+        }
+    }
+
+    /// 0031
+    /// path: 不符合约束的值的完整路径.
+    /// constraint: StringConstraint::describe(), 例如: format `email`.
+    pub fn err_string_constraint(
+        file_path: String,
+        span: Span,
+        path: &str,
+        constraint: &str,
+        found: &str,
+    ) -> Self {
+        Self {
+            error_code: 0031,
+            file_path,
+            msg: format!(
+                "value of `{}` does not satisfy {}, found `{}`",
+                path, constraint, found
+            ),
+            span,
+            note: None,
+            help: None,
+        }
+    }
+
+    /// 0032
+    /// path: 数组的完整路径.
+    pub fn err_array_length(
+        file_path: String,
+        span: Span,
+        path: &str,
+        range: &str,
+        found_len: usize,
+    ) -> Self {
+        Self {
+            error_code: 0032,
+            file_path,
+            msg: format!(
+                "length of `{}` is out of range, expected `{}` found `{}`",
+                path, range, found_len
+            ),
+            span,
+            note: None,
+            help: Some(format!("允许的元素数量是 `{}`", range)),
+        }
+    }
+
+    /// 0033
+    /// path: 重复的元素的完整路径, first_path: 第一次出现的位置, 例如 `tags[2]` 和 `tags[0]`.
+    pub fn err_array_duplicated_element(
+        file_path: String,
+        span: Span,
+        path: &str,
+        first_path: &str,
+    ) -> Self {
+        Self {
+            error_code: 0033,
+            file_path,
+            msg: format!("duplicated element `{}`, same as `{}`", path, first_path),
+            span,
+            note: Some(format!("数组的元素需要是唯一的")),
+            help: None,
+        }
+    }
+
+    /// 0034
+    pub fn err_unknow_array_constraint(file_path: String, span: Span, name: &str) -> Self {
+        Self {
+            error_code: 0034,
+            file_path,
+            msg: format!("unknow array constraint `{}`", name),
+            span,
+            note: Some(format!("数组的约束可以是元素数量的范围或者 unique")),
+            help: did_you_mean(name, &["unique"]),
+        }
+    }
+
+    /// 0035
+    /// 通过了类型检查, 但是无法转换为 rust 的类型.
+    /// path: 出错的字段的路径, msg: serde 的错误信息.
+    pub fn err_deserialize(file_path: String, span: Span, path: &str, msg: &str) -> Self {
+        let msg = if path.is_empty() {
+            msg.to_string()
+        } else {
+            format!("{} at `{}`", msg, path)
+        };
+
+        Self {
+            error_code: 0035,
+            file_path,
+            msg,
+            span,
+            note: None,
+            help: None,
+        }
+    }
+}
+
+/// 从 candidates 中找出跟 name 相似的名字, 例如: did you mean `name`?
+fn did_you_mean(name: &str, candidates: &[&str]) -> Option<String> {
+    // 允许的最大编辑距离, 太短的名字只允许一个字符的差别.
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    let mut similar: Vec<(usize, &str)> = candidates
+        .iter()
+        .map(|x| (edit_distance(name, x), *x))
+        .filter(|x| x.0 <= max_distance)
+        .collect();
+
+    if similar.is_empty() {
+        return None;
+    }
+
+    similar.sort();
+
+    let names: Vec<String> = similar
+        .iter()
+        .take(3)
+        .map(|x| format!("`{}`", x.1))
+        .collect();
+
+    return Some(format!("did you mean {}?", names.join(", ")));
+}
+
+/// 编辑距离 (optimal string alignment), 按字符计算.
+/// 相邻两个字符交换位置也算一次编辑, 例如 `nmae` -> `name`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // d[i][j]: a[..i] 和 b[..j] 的编辑距离.
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, x) in d[0].iter_mut().enumerate() {
+        *x = j;
+    }
+
+    for (i, ca) in a.iter().enumerate().map(|(i, c)| (i + 1, c)) {
+        for (j, cb) in b.iter().enumerate().map(|(j, c)| (j + 1, c)) {
+            let cost = if ca == cb { 0 } else { 1 };
+
+            let mut dist = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && *ca == b[j - 2] && a[i - 2] == *cb {
+                dist = dist.min(d[i - 2][j - 2] + 1);
+            }
+
+            d[i][j] = dist;
+        }
+    }
+
+    return d[a.len()][b.len()];
 }